@@ -0,0 +1,141 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::{api, gw};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CountryError {
+    InvalidCode(String),
+}
+
+// ISO 3166-1 alpha-2 code, always uppercase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Country([u8; 2]);
+
+impl Country {
+    pub fn new(code: &str) -> Result<Self, CountryError> {
+        match code.as_bytes() {
+            [a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => {
+                Ok(Country([a.to_ascii_uppercase(), b.to_ascii_uppercase()]))
+            }
+            _ => Err(CountryError::InvalidCode(code.to_string())),
+        }
+    }
+
+    // Country of the caller, as seen by deezer
+    pub fn from_info(info: &api::Info) -> Result<Self, CountryError> {
+        Country::new(&info.country_iso)
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ascii letters are stored
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    fn is_in(&self, codes: &[String]) -> bool {
+        codes
+            .iter()
+            .any(|code| code.eq_ignore_ascii_case(self.as_str()))
+    }
+}
+
+impl FromStr for Country {
+    type Err = CountryError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Country::new(code)
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tier {
+    // free listening with ads, gw STREAM_ADS
+    Free,
+    // paid subscription, which also gets everything free users get
+    Premium,
+}
+
+pub trait Availability {
+    fn is_available_in(&self, country: Country, tier: Tier) -> bool;
+}
+
+impl Availability for gw::AvailableCountries {
+    fn is_available_in(&self, country: Country, tier: Tier) -> bool {
+        match tier {
+            Tier::Free => country.is_in(&self.stream_ads),
+            Tier::Premium => {
+                country.is_in(&self.stream_ads) || country.is_in(&self.stream_sub_only)
+            }
+        }
+    }
+}
+
+impl Availability for gw::Track {
+    fn is_available_in(&self, country: Country, tier: Tier) -> bool {
+        self.available_countries.is_available_in(country, tier)
+    }
+}
+
+// The public api does not tell tiers apart, the tier is ignored
+impl Availability for api::MainTrack {
+    fn is_available_in(&self, country: Country, _tier: Tier) -> bool {
+        country.is_in(&self.available_countries)
+    }
+}
+
+// An album or song list is available when one of its songs is
+impl Availability for gw::SongListData {
+    fn is_available_in(&self, country: Country, tier: Tier) -> bool {
+        self.songs
+            .iter()
+            .flatten()
+            .any(|song| song.is_available_in(country, tier))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    // keep unavailable songs, only listing their ids
+    Flag,
+    // remove unavailable songs and list their ids
+    Drop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountryFilter {
+    pub country: Country,
+    pub tier: Tier,
+    pub mode: FilterMode,
+}
+
+impl CountryFilter {
+    pub fn new(country: Country, tier: Tier, mode: FilterMode) -> Self {
+        Self {
+            country,
+            tier,
+            mode,
+        }
+    }
+
+    // Fills unavailable_ids, count and total are left as deezer returned them
+    pub fn apply(&self, list: &mut gw::SongListData) {
+        let songs = match &mut list.songs {
+            Some(songs) => songs,
+            None => return,
+        };
+        list.unavailable_ids = songs
+            .iter()
+            .filter(|song| !song.is_available_in(self.country, self.tier))
+            .map(|song| song.sng_id)
+            .collect();
+        if self.mode == FilterMode::Drop {
+            songs.retain(|song| song.is_available_in(self.country, self.tier));
+        }
+    }
+}
@@ -1,510 +1,419 @@
-use reqwest::blocking::{Client, Response};
-use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, USER_AGENT};
-use reqwest::{header, Error as RequestError, Url};
-use serde::de::DeserializeOwned;
-use serde_json::{json, Error as JsonError, Value};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use url::ParseError;
-
+use std::future::Future;
+#[cfg(feature = "tagging")]
+use std::path::Path;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::availability::CountryFilter;
+use crate::cache::{CacheConfig, CacheStats};
+use crate::hydrate::Hydrate;
+use crate::link::{DeezerResource, Resolved};
+use crate::metrics::MetricsSnapshot;
+use crate::middleware::Middleware;
 use crate::models;
-use crate::{API_URL, GW_API_URL, USER_AGENT_HEADER};
-
-#[derive(Debug, Clone, Default)]
+use crate::models::ids::{
+    AlbumId, ArtistId, GenreId, Isrc, PlaylistId, TrackId, Upc,
+};
+use crate::transport::Transport;
+pub use crate::DeezerError;
+#[cfg(feature = "tagging")]
+use crate::{library, tagging};
+
+// Runs the async client on its own single threaded runtime, so every
+// endpoint is written once in lib.rs
+#[derive(Debug, Clone)]
 pub struct Deezer {
-    client: Client,
-    token: Arc<Mutex<String>>,
-}
-
-#[derive(Debug)]
-pub enum DeezerError {
-    JsonError(JsonError),
-    RequestError(RequestError),
-    ApiError(String),
-    ParseError(ParseError),
+    inner: crate::Deezer,
+    runtime: Arc<Runtime>,
 }
 
-fn parse_response_to_value(response: Response) -> Result<Value, DeezerError> {
-    let body = match response.text() {
-        Ok(v) => v,
-        Err(err) => return Err(DeezerError::RequestError(err)),
-    };
-    let value: Value = match serde_json::from_str(body.as_str()) {
-        Ok(v) => v,
-        Err(err) => return Err(DeezerError::JsonError(err)),
-    };
-    match value.get("error") {
-        Some(error_value) => {
-            if error_value.is_object() {
-                return Err(DeezerError::ApiError(error_value.to_string()));
-            }
-        }
-        None => (),
-    };
-    Ok(value)
+impl Default for Deezer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Deezer {
     pub fn new() -> Self {
-        let mut headers = header::HeaderMap::new();
-        headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_HEADER));
-        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en"));
-
-        let client = Client::builder()
-            .cookie_store(true)
-            .default_headers(headers)
-            .timeout(Duration::from_secs(15))
+        let runtime = Builder::new_current_thread()
+            .enable_all()
             .build()
-            .expect("Could not establish client!");
-
+            .expect("Could not start runtime!");
         Self {
-            client,
-            token: Arc::new(Mutex::new(String::from("null"))),
+            inner: crate::Deezer::new(),
+            runtime: Arc::new(runtime),
         }
     }
 
-    pub fn refresh_token(&self) -> String {
-        let empty_token = String::from("null");
-        let response = self.gw_method_call("deezer.getUserData");
-        match response {
-            Ok(resp) => match resp.text() {
-                Ok(body) => match serde_json::from_str(body.as_str())
-                    as Result<Value, JsonError>
-                {
-                    Ok(value) => match value["results"]["checkForm"].as_str() {
-                        Some(new_token) => {
-                            let new_token = new_token.to_owned();
-                            let mut token = self.token.lock().unwrap();
-                            *token = new_token.clone();
-                            new_token
-                        }
-                        None => empty_token,
-                    },
-                    Err(_) => empty_token,
-                },
-                Err(_) => empty_token,
-            },
-            Err(_) => empty_token,
-        }
+    pub fn with_transport(
+        mut self,
+        transport: impl Transport + 'static,
+    ) -> Self {
+        self.inner = self.inner.with_transport(transport);
+        self
     }
 
-    fn method_call(&self, path: &str) -> Result<Response, DeezerError> {
-        let mut url = match Url::parse(API_URL) {
-            Ok(base_url) => match base_url.join(path) {
-                Ok(url) => url,
-                Err(error) => return Err(DeezerError::ParseError(error)),
-            },
-            Err(error) => return Err(DeezerError::ParseError(error)),
-        };
-        let mut params: HashMap<String, String> = url
-            .query_pairs()
-            .map(|x| (x.0.into_owned(), x.1.into_owned()))
-            .collect();
-        params
-            .entry("limit".to_string())
-            .or_insert("100".to_string());
-        url.query_pairs_mut()
-            .clear()
-            .extend_pairs(params.into_iter());
-        let response = self.client.get(url).send();
-        match response {
-            Ok(r) => Ok(r),
-            Err(error) => Err(DeezerError::RequestError(error)),
-        }
+    pub fn with_middleware(
+        mut self,
+        middleware: impl Middleware + 'static,
+    ) -> Self {
+        self.inner = self.inner.with_middleware(middleware);
+        self
     }
 
-    fn gw_method_call(&self, method: &str) -> Result<Response, RequestError> {
-        let mut token = self.token.lock().unwrap().to_owned();
-        if token == "null" && !(method == "deezer.getUserData") {
-            self.refresh_token();
-            token = self.token.lock().unwrap().to_owned();
-        }
+    // Requests run by the bulk methods such as tracks(ids) at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.inner = self.inner.with_concurrency(concurrency);
+        self
+    }
+
+    // Clones made after this share the cache
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.inner = self.inner.with_cache(config);
+        self
+    }
 
-        let api_token = match method {
-            "deezer.getUserData" => "null".to_owned(),
-            _ => token,
-        };
+    // Panics when called from within an async runtime, use crate::Deezer there
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
 
-        let mut params = HashMap::new();
-        params.insert("api_token", api_token);
-        params.insert("api_version", "1.0".to_owned());
-        params.insert("method", method.to_owned());
-        params.insert("input", params.len().to_string());
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.inner.cache_stats()
+    }
 
-        let response = self.client.post(GW_API_URL).form(&params).send()?;
-        Ok(response)
+    pub fn in_flight_requests(&self) -> usize {
+        self.inner.in_flight_requests()
     }
 
-    fn gw_method_call_params(
-        &self,
-        method: &str,
-        params: HashMap<&str, String>,
-    ) -> Result<Response, RequestError> {
-        let mut token = self.token.lock().unwrap().to_owned();
-        if token == "null" && !(method == "deezer.getUserData") {
-            self.refresh_token();
-            token = self.token.lock().unwrap().to_owned();
+    pub fn with_country_filter(mut self, filter: CountryFilter) -> Self {
+        self.inner = self.inner.with_country_filter(filter);
+        self
+    }
+
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.inner = self.inner.with_language(language);
+        self
+    }
+
+    pub fn in_language(&self, language: &str) -> Self {
+        Self {
+            inner: self.inner.in_language(language),
+            runtime: self.runtime.clone(),
         }
+    }
 
-        let api_token = match method {
-            "deezer.getUserData" => "null".to_owned(),
-            _ => token,
-        };
+    pub fn language(&self) -> &str {
+        self.inner.language()
+    }
 
-        let mut params = params.clone();
-        params.insert("api_token", api_token);
-        params.insert("api_version", "1.0".to_owned());
-        params.insert("method", method.to_owned());
-        params.insert("input", params.len().to_string());
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.inner.metrics()
+    }
 
-        let response = self.client.post(GW_API_URL).form(&params).send()?;
-        Ok(response)
+    pub fn reset_metrics(&self) {
+        self.inner.reset_metrics()
     }
 
-    fn gw_method_call_body(
-        &self,
-        method: &str,
-        body: &Value,
-    ) -> Result<Response, RequestError> {
-        let mut token = self.token.lock().unwrap().to_owned();
-        if token == "null" && !(method == "deezer.getUserData") {
-            self.refresh_token();
-            token = self.token.lock().unwrap().to_owned();
-        }
+    pub fn clear_cache(&self) {
+        self.inner.clear_cache()
+    }
 
-        let api_token = match method {
-            "deezer.getUserData" => "null".to_owned(),
-            _ => token,
-        };
-
-        let mut params = HashMap::new();
-        params.insert("api_token", api_token.clone());
-        params.insert("api_version", "1.0".to_owned());
-        params.insert("method", method.to_owned());
-        params.insert("input", params.len().to_string());
-
-        let url = Url::parse_with_params(GW_API_URL, params).unwrap();
-
-        let response = self.client.post(url).json(body).send()?;
-        Ok(response)
-    }
-
-    fn call_deserialize_gw_request_with_params<T: DeserializeOwned>(
-        &self,
-        method: &str,
-        params: HashMap<&str, String>,
-    ) -> Result<T, DeezerError> {
-        let response: Response =
-            match self.gw_method_call_params(method, params) {
-                Ok(r) => r,
-                Err(err) => return Err(DeezerError::RequestError(err)),
-            };
-        let value: Value = parse_response_to_value(response)?;
-        match serde_json::from_value(value["results"].clone()) {
-            Ok(v) => Ok(v),
-            Err(err) => return Err(DeezerError::JsonError(err)),
-        }
+    pub fn invalidate_cache_endpoint(&self, endpoint: &str) {
+        self.inner.invalidate_cache_endpoint(endpoint)
     }
 
-    fn call_deserialize_api_request<T: DeserializeOwned>(
-        &self,
-        url_path: &str,
-    ) -> Result<T, DeezerError> {
-        let response: Response = self.method_call(url_path)?;
-        let value: Value = parse_response_to_value(response)?;
-        match serde_json::from_value(value) {
-            Ok(v) => Ok(v),
-            Err(err) => return Err(DeezerError::JsonError(err)),
-        }
+    pub fn invalidate_cache_path(&self, path: &str) {
+        self.inner.invalidate_cache_path(path)
     }
 
-    fn call_deserialize_api_request_as_vec<T: DeserializeOwned>(
-        &self,
-        url_path: &str,
-    ) -> Result<Vec<T>, DeezerError> {
-        let mut objects: Vec<T> = Vec::new();
-        let mut url_path = url_path.to_string();
-        loop {
-            let response: Response = self.method_call(url_path.as_str())?;
-            let value: Value = parse_response_to_value(response)?;
-            let result: Vec<T> =
-                match serde_json::from_value(value["data"].clone()) {
-                    Ok(v) => v,
-                    Err(err) => return Err(DeezerError::JsonError(err)),
-                };
-            objects.extend(result);
-            if let Some(next) = value.get("next") {
-                url_path = match next.as_str() {
-                    Some(next_url) => next_url.to_string(),
-                    None => break,
-                };
-            } else {
-                break;
-            }
-        }
-        Ok(objects)
+    pub fn refresh_token(&self) -> String {
+        self.block_on(self.inner.refresh_token())
     }
 
     pub fn gw_track(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::gw::TrackData, DeezerError> {
-        let params: HashMap<&str, String> =
-            [("sng_id", song_id.to_string())].into();
-        self.call_deserialize_gw_request_with_params("deezer.pageTrack", params)
+        self.block_on(self.inner.gw_track(song_id))
     }
 
     pub fn gw_song(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::gw::Song, DeezerError> {
-        let params: HashMap<&str, String> =
-            [("sng_id", song_id.to_string())].into();
-        self.call_deserialize_gw_request_with_params("song.getData", params)
+        self.block_on(self.inner.gw_song(song_id))
     }
 
     pub fn gw_songs(
         &self,
-        song_ids: &Vec<u64>,
+        song_ids: &[TrackId],
     ) -> Result<models::gw::SongListData, DeezerError> {
-        let body: Value = json!({"sng_ids": song_ids});
-        let response: Response =
-            match self.gw_method_call_body("song.getListData", &body) {
-                Ok(r) => r,
-                Err(err) => return Err(DeezerError::RequestError(err)),
-            };
-        let value: Value = parse_response_to_value(response)?;
-        match serde_json::from_value(value["results"].clone()) {
-            Ok(songs) => Ok(songs),
-            Err(err) => return Err(DeezerError::JsonError(err)),
-        }
+        self.block_on(self.inner.gw_songs(song_ids))
     }
 
     pub fn gw_songs_from_album(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<models::gw::SongListData, DeezerError> {
-        let params: HashMap<&str, String> =
-            [("alb_id", album_id.to_string())].into();
-        self.call_deserialize_gw_request_with_params(
-            "song.getListByAlbum",
-            params,
-        )
+        self.block_on(self.inner.gw_songs_from_album(album_id))
     }
 
     pub fn gw_album(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<models::gw::Album, DeezerError> {
-        let params: HashMap<&str, String> =
-            [("alb_id", album_id.to_string())].into();
-        self.call_deserialize_gw_request_with_params("album.getData", params)
+        self.block_on(self.inner.gw_album(album_id))
     }
 
     pub fn gw_lyrics(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::gw::Lyrics, DeezerError> {
-        let params: HashMap<&str, String> =
-            [("sng_id", song_id.to_string())].into();
-        self.call_deserialize_gw_request_with_params("song.getLyrics", params)
-    }
-
-    // TODO payload is huge, models need to be implemented to match returned json, and takes a lot of time
-    // pub fn gw_artist(&self, artist_id: u64, lang: &str) -> Result<models::gw::ArtistResults, DeezerError> {
-    //     let params: HashMap<&str, String> = [("art_id", artist_id.to_string()), ("lang", lang.to_string())].into();
-    //     let response = match self.gw_method_call_params("deezer.pageArtist", params) {
-    //         Ok(r) => r,
-    //         Err(err) => return Err(DeezerError::RequestError(err)),
-    //     };
-    //     let value: Value = parse_response(response)?;
-    //     let track = match serde_json::from_value(value["results"].clone()) {
-    //         Ok(v) => v,
-    //         Err(err) => return Err(DeezerError::JsonError(err)),
-    //     };
-    //     Ok(track)
-    // }
+        self.block_on(self.inner.gw_lyrics(song_id))
+    }
 
     pub fn track(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::api::MainTrack, DeezerError> {
-        self.call_deserialize_api_request(format!("track/{}", song_id).as_str())
+        self.block_on(self.inner.track(song_id))
+    }
+
+    pub fn track_full(
+        &self,
+        song_id: TrackId,
+    ) -> Result<models::domain::FullTrack, DeezerError> {
+        self.block_on(self.inner.track_full(song_id))
     }
 
     pub fn track_from_isrc(
         &self,
-        isrc: &str,
+        isrc: &Isrc,
     ) -> Result<models::api::MainTrack, DeezerError> {
-        self.call_deserialize_api_request(
-            format!("track/isrc:{}", isrc).as_str(),
-        )
+        self.block_on(self.inner.track_from_isrc(isrc))
+    }
+
+    pub fn tracks(
+        &self,
+        song_ids: &[TrackId],
+    ) -> Vec<Result<models::api::MainTrack, DeezerError>> {
+        self.block_on(self.inner.tracks(song_ids))
+    }
+
+    pub fn tracks_from_isrcs(
+        &self,
+        isrcs: &[Isrc],
+    ) -> Vec<Result<models::api::MainTrack, DeezerError>> {
+        self.block_on(self.inner.tracks_from_isrcs(isrcs))
     }
 
     pub fn album(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<models::api::MainAlbum, DeezerError> {
-        self.call_deserialize_api_request(
-            format!("album/{}", album_id).as_str(),
-        )
+        self.block_on(self.inner.album(album_id))
+    }
+
+    pub fn albums(
+        &self,
+        album_ids: &[AlbumId],
+    ) -> Vec<Result<models::api::MainAlbum, DeezerError>> {
+        self.block_on(self.inner.albums(album_ids))
     }
 
     pub fn album_from_upc(
         &self,
-        upc: u64,
+        upc: &Upc,
     ) -> Result<models::api::MainAlbum, DeezerError> {
-        self.call_deserialize_api_request(format!("album/upc:{}", upc).as_str())
+        self.block_on(self.inner.album_from_upc(upc))
     }
 
     pub fn album_tracks(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("album/{}/tracks", album_id).as_str(),
-        )
+        self.block_on(self.inner.album_tracks(album_id))
     }
 
     pub fn artist(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<models::api::Artist, DeezerError> {
-        self.call_deserialize_api_request(
-            format!("artist/{}", artist_id).as_str(),
-        )
+        self.block_on(self.inner.artist(artist_id))
+    }
+
+    pub fn artists(
+        &self,
+        artist_ids: &[ArtistId],
+    ) -> Vec<Result<models::api::Artist, DeezerError>> {
+        self.block_on(self.inner.artists(artist_ids))
     }
 
     pub fn artist_albums(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Album>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("artist/{}/albums", artist_id).as_str(),
-        )
+        self.block_on(self.inner.artist_albums(artist_id))
     }
 
     pub fn artist_top_tracks(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("artist/{}/top", artist_id).as_str(),
-        )
+        self.block_on(self.inner.artist_top_tracks(artist_id))
     }
 
     pub fn artist_related_artists(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::RelatedArtist>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("artist/{}/related", artist_id).as_str(),
-        )
+        self.block_on(self.inner.artist_related_artists(artist_id))
     }
 
     pub fn artist_radio(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("artist/{}/radio", artist_id).as_str(),
-        )
+        self.block_on(self.inner.artist_radio(artist_id))
     }
 
     pub fn artist_playlists(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Playlist>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("artist/{}/playlists", artist_id).as_str(),
-        )
+        self.block_on(self.inner.artist_playlists(artist_id))
     }
 
     pub fn editorial(
         &self,
     ) -> Result<Vec<models::api::Editorial>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("editorial")
+        self.block_on(self.inner.editorial())
     }
 
     pub fn editorial_from_genre(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<models::api::Editorial, DeezerError> {
-        self.call_deserialize_api_request(
-            format!("editorial/{}", genre_id).as_str(),
-        )
+        self.block_on(self.inner.editorial_from_genre(genre_id))
     }
 
     pub fn genres(&self) -> Result<Vec<models::api::Editorial>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("genre")
+        self.block_on(self.inner.genres())
     }
 
     pub fn genre(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<models::api::Editorial, DeezerError> {
-        self.call_deserialize_api_request(
-            format!("genre/{}", genre_id).as_str(),
-        )
+        self.block_on(self.inner.genre(genre_id))
     }
 
     pub fn genre_artists(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<Vec<models::api::Artist>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("genre/{}/artists", genre_id).as_str(),
-        )
+        self.block_on(self.inner.genre_artists(genre_id))
     }
 
     pub fn genre_radios(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<Vec<models::api::Radio>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("genre/{}/radios", genre_id).as_str(),
-        )
+        self.block_on(self.inner.genre_radios(genre_id))
     }
 
     pub fn infos(&self) -> Result<models::api::Info, DeezerError> {
-        self.call_deserialize_api_request("infos")
+        self.block_on(self.inner.infos())
     }
 
     pub fn radios(&self) -> Result<Vec<models::api::Radio>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("radio")
+        self.block_on(self.inner.radios())
     }
 
     pub fn radio(
         &self,
         radio_id: u64,
     ) -> Result<models::api::Radio, DeezerError> {
-        self.call_deserialize_api_request(
-            format!("radio/{}", radio_id).as_str(),
-        )
+        self.block_on(self.inner.radio(radio_id))
     }
 
     pub fn radio_tracks(
         &self,
         radio_id: u64,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
-        self.call_deserialize_api_request_as_vec(
-            format!("radio/{}/tracks", radio_id).as_str(),
-        )
+        self.block_on(self.inner.radio_tracks(radio_id))
     }
 
     pub fn radio_genres(
         &self,
     ) -> Result<Vec<models::api::GenreRadios>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("radio/genres")
+        self.block_on(self.inner.radio_genres())
     }
 
     pub fn radio_top(&self) -> Result<Vec<models::api::Radio>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("radio/top")
+        self.block_on(self.inner.radio_top())
     }
 
     pub fn radio_lists(&self) -> Result<Vec<models::api::Radio>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("radio/lists")
+        self.block_on(self.inner.radio_lists())
+    }
+
+    pub fn playlist(
+        &self,
+        playlist_id: PlaylistId,
+    ) -> Result<models::api::MainPlaylist, DeezerError> {
+        self.block_on(self.inner.playlist(playlist_id))
+    }
+
+    pub fn user(&self, user_id: u64) -> Result<models::api::User, DeezerError> {
+        self.block_on(self.inner.user(user_id))
+    }
+
+    pub fn podcast(
+        &self,
+        podcast_id: u64,
+    ) -> Result<models::api::Podcast, DeezerError> {
+        self.block_on(self.inner.podcast(podcast_id))
+    }
+
+    pub fn episode(
+        &self,
+        episode_id: u64,
+    ) -> Result<models::api::Episode, DeezerError> {
+        self.block_on(self.inner.episode(episode_id))
+    }
+
+    pub fn tracklist(
+        &self,
+        url: &str,
+    ) -> Result<Vec<models::api::Track>, DeezerError> {
+        self.block_on(self.inner.tracklist(url))
+    }
+
+    pub fn hydrate<T: Hydrate>(
+        &self,
+        item: &T,
+    ) -> Result<T::Full, DeezerError> {
+        self.block_on(self.inner.hydrate(item))
+    }
+
+    pub fn hydrate_all<T: Hydrate>(
+        &self,
+        items: &[T],
+    ) -> Vec<Result<T::Full, DeezerError>> {
+        self.block_on(self.inner.hydrate_all(items))
+    }
+
+    pub fn resolve_link(
+        &self,
+        url: &str,
+    ) -> Result<DeezerResource, DeezerError> {
+        self.block_on(self.inner.resolve_link(url))
+    }
+
+    pub fn resolve(&self, url: &str) -> Result<Resolved, DeezerError> {
+        self.block_on(self.inner.resolve(url))
     }
 
     pub fn search(
@@ -512,13 +421,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
-        };
-        self.call_deserialize_api_request_as_vec(
-            format!("search?q={query}&strict={strict}").as_str(),
-        )
+        self.block_on(self.inner.search(query, strict))
     }
 
     pub fn search_album(
@@ -526,13 +429,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Album>, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
-        };
-        self.call_deserialize_api_request_as_vec(
-            format!("search/album?q={query}&strict={strict}").as_str(),
-        )
+        self.block_on(self.inner.search_album(query, strict))
     }
 
     pub fn search_artist(
@@ -540,13 +437,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Artist>, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
-        };
-        self.call_deserialize_api_request_as_vec(
-            format!("search/artist?q={query}&strict={strict}").as_str(),
-        )
+        self.block_on(self.inner.search_artist(query, strict))
     }
 
     pub fn search_playlist(
@@ -554,13 +445,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Playlist>, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
-        };
-        self.call_deserialize_api_request_as_vec(
-            format!("search/playlist?q={query}&strict={strict}").as_str(),
-        )
+        self.block_on(self.inner.search_playlist(query, strict))
     }
 
     pub fn search_user(
@@ -568,13 +453,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::User>, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
-        };
-        self.call_deserialize_api_request_as_vec(
-            format!("search/user?q={query}&strict={strict}").as_str(),
-        )
+        self.block_on(self.inner.search_user(query, strict))
     }
 
     pub fn search_track(
@@ -584,29 +463,31 @@ impl Deezer {
         album: &str,
         strict: bool,
     ) -> Result<models::api::Track, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
-        };
-        let searches: Vec<models::api::Track> = self.call_deserialize_api_request_as_vec(
-            format!(r#"search/track?q=track:"{track}" artist:"{artist}" album:"{album}"&strict={strict}"#).as_str(),
-        )?;
-        if let Some(_) = searches.get(0) {
-            return Ok(searches[0].to_owned());
-        }
-        let searches: Vec<models::api::Track> =
-            self.call_deserialize_api_request_as_vec(format!(r#"search/track?q=track:"{track}" artist:"{artist}"&strict={strict}"#).as_str())?;
-        if let Some(_) = searches.get(0) {
-            return Ok(searches[0].to_owned());
-        }
-        let searches: Vec<models::api::Track> = self
-            .call_deserialize_api_request_as_vec(
-                format!(r#"search/track?q=track:"{track}"&strict={strict}"#)
-                    .as_str(),
-            )?;
-        if let Some(_) = searches.get(0) {
-            return Ok(searches[0].to_owned());
-        }
-        Err(DeezerError::ApiError("No Track Found".to_string()))
+        self.block_on(self.inner.search_track(track, artist, album, strict))
+    }
+
+    #[cfg(feature = "tagging")]
+    pub fn cover_art(
+        &self,
+        url: &str,
+    ) -> Result<tagging::Picture, DeezerError> {
+        self.block_on(self.inner.cover_art(url))
+    }
+
+    #[cfg(feature = "tagging")]
+    pub fn match_local_track(
+        &self,
+        local: &library::LocalTrack,
+    ) -> library::FileMatch {
+        self.block_on(self.inner.match_local_track(local))
+    }
+
+    #[cfg(feature = "tagging")]
+    pub fn match_library(
+        &self,
+        dir: impl AsRef<Path>,
+        report_path: impl AsRef<Path>,
+    ) -> Result<library::MatchReport, library::LibraryError> {
+        self.block_on(self.inner.match_library(dir, report_path))
     }
 }
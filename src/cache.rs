@@ -0,0 +1,476 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::fs;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{API_URL, DEFAULT_LANGUAGE};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedBody {
+    // raw json as returned by deezer
+    pub body: String,
+    pub fetched_at: SystemTime,
+}
+
+// Persistent second level behind the in-memory cache, keys are the in-memory cache keys
+pub trait CacheStorage: Debug + Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<CachedBody>, IoError>;
+    fn put(&self, key: &str, body: &CachedBody) -> Result<(), IoError>;
+    fn remove(&self, key: &str) -> Result<(), IoError>;
+    fn clear(&self) -> Result<(), IoError>;
+    fn keys(&self) -> Result<Vec<String>, IoError>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredFile {
+    key: String,
+    fetched_at: u64,
+    body: String,
+}
+
+// One json file per key in a directory, named after a stable hash of the key,
+// with the key alone in a .key file next to it so keys are listed without
+// reading the bodies
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+// FNV-1a, the std hasher is not guaranteed to be stable across releases
+fn stable_hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl FileStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, IoError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", stable_hash(key)))
+    }
+
+    // Files written before the .key files existed only have the key inside
+    fn stored_key(path: &Path) -> Option<String> {
+        match fs::read_to_string(path.with_extension("key")) {
+            Ok(key) => Some(key),
+            Err(_) => {
+                let stored: StoredFile = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+                Some(stored.key)
+            }
+        }
+    }
+}
+
+impl CacheStorage for FileStorage {
+    fn get(&self, key: &str) -> Result<Option<CachedBody>, IoError> {
+        let data = match fs::read(self.path(key)) {
+            Ok(data) => data,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let stored: StoredFile = serde_json::from_slice(&data)?;
+        // a hash collision reads as a miss
+        if stored.key != key {
+            return Ok(None);
+        }
+        Ok(Some(CachedBody {
+            body: stored.body,
+            fetched_at: UNIX_EPOCH + Duration::from_secs(stored.fetched_at),
+        }))
+    }
+
+    fn put(&self, key: &str, body: &CachedBody) -> Result<(), IoError> {
+        let stored = StoredFile {
+            key: key.to_string(),
+            fetched_at: body
+                .fetched_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            body: body.body.clone(),
+        };
+        let path = self.path(key);
+        let temp = path.with_extension("tmp");
+        fs::write(path.with_extension("key"), key)?;
+        fs::write(&temp, serde_json::to_vec(&stored)?)?;
+        fs::rename(temp, path)
+    }
+
+    fn remove(&self, key: &str) -> Result<(), IoError> {
+        let path = self.path(key);
+        for path in [path.with_extension("key"), path] {
+            match fs::remove_file(path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), IoError> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "key")
+            {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Files which cannot be read are left out, they read as misses anyway
+    fn keys(&self) -> Result<Vec<String>, IoError> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let Ok(entry) = entry else { continue };
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                keys.extend(Self::stored_key(&path));
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub capacity: usize,
+    pub default_ttl: Duration,
+    // endpoint name ("track", "search", "song.getData") to ttl, a zero ttl disables caching
+    pub ttls: HashMap<String, Duration>,
+    pub storage: Option<Arc<dyn CacheStorage>>,
+    // answer from the cache only, whatever the age of the entries, and never fetch
+    pub offline: bool,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self::new(10_000, Duration::from_secs(60 * 60))
+    }
+}
+
+impl CacheConfig {
+    pub fn new(capacity: usize, default_ttl: Duration) -> Self {
+        Self {
+            capacity,
+            default_ttl,
+            ttls: HashMap::new(),
+            storage: None,
+            offline: false,
+        }
+    }
+
+    pub fn with_ttl(mut self, endpoint: &str, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint.to_string(), ttl);
+        self
+    }
+
+    pub fn with_storage(mut self, storage: impl CacheStorage + 'static) -> Self {
+        self.storage = Some(Arc::new(storage));
+        self
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn ttl(&self, endpoint: &str) -> Duration {
+        self.ttls.get(endpoint).copied().unwrap_or(self.default_ttl)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+    // hits answered by the storage, also counted in hits
+    pub storage_hits: u64,
+    pub storage_errors: u64,
+    pub entries: usize,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Value,
+    expires: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    entries: HashMap<String, Entry>,
+    // last_used tick to key, the first one is the least recently used
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl Entries {
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.recency.remove(&entry.last_used);
+        Some(entry)
+    }
+
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.recency.insert(tick, key.to_string());
+        }
+    }
+}
+
+// Successful responses only, keyed by api path or gw method and parameters
+#[derive(Debug, Default)]
+pub(crate) struct ResponseCache {
+    config: CacheConfig,
+    entries: Mutex<Entries>,
+}
+
+// "track/3135556?limit=100" -> "track", full urls from pagination are accepted too
+pub(crate) fn api_endpoint(path: &str) -> &str {
+    let path = path.strip_prefix(API_URL).unwrap_or(path);
+    let path = path.split(['?', '/']).next().unwrap_or(path);
+    path.trim_start_matches('/')
+}
+
+// Endpoint name of a cache key, the api endpoint or the gw method
+fn key_endpoint(key: &str) -> &str {
+    match key.strip_prefix("gw:") {
+        Some(method) => method.split('?').next().unwrap_or(method),
+        None => api_endpoint(key.strip_prefix("api:").unwrap_or(key)),
+    }
+}
+
+// Responses depend on the language, keys only carry it when it is not the
+// default so that caches stored before stay valid
+pub(crate) fn localized_key(key: String, language: &str) -> String {
+    if language == DEFAULT_LANGUAGE {
+        return key;
+    }
+    let separator = if key.contains('?') { '&' } else { '?' };
+    format!("{key}{separator}lang={language}")
+}
+
+pub(crate) fn api_key(path: &str) -> String {
+    format!("api:{}", path.strip_prefix(API_URL).unwrap_or(path))
+}
+
+pub(crate) fn gw_key<'a>(
+    method: &str,
+    params: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> String {
+    let mut params: Vec<(&str, &str)> = params.into_iter().collect();
+    params.sort();
+    let params: Vec<String> = params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    format!("gw:{method}?{}", params.join("&"))
+}
+
+impl ResponseCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // the entries stay consistent even if a holder panicked
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.config.offline
+    }
+
+    // get and insert do file io when there is a storage
+    pub fn has_storage(&self) -> bool {
+        self.config.storage.is_some()
+    }
+
+    pub fn get(&self, key: &str, endpoint: &str) -> Option<Value> {
+        if let Some(value) = self.memory_get(key) {
+            self.lock().stats.hits += 1;
+            return Some(value);
+        }
+        match self.storage_get(key, endpoint) {
+            Some(value) => {
+                let mut entries = self.lock();
+                entries.stats.hits += 1;
+                entries.stats.storage_hits += 1;
+                Some(value)
+            }
+            None => {
+                self.lock().stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn memory_get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.lock();
+        let expired = entries.entries.get(key)?.expires <= Instant::now();
+        if expired && !self.config.offline {
+            entries.remove(key);
+            entries.stats.expirations += 1;
+            return None;
+        }
+        entries.touch(key);
+        entries.entries.get(key).map(|entry| entry.value.clone())
+    }
+
+    // Fresh enough stored bodies are moved into memory for the rest of their ttl
+    fn storage_get(&self, key: &str, endpoint: &str) -> Option<Value> {
+        let storage = self.config.storage.as_ref()?;
+        let cached = match storage.get(key) {
+            Ok(cached) => cached?,
+            Err(_) => {
+                self.lock().stats.storage_errors += 1;
+                return None;
+            }
+        };
+        let age = cached.fetched_at.elapsed().unwrap_or_default();
+        let ttl = self.config.ttl(endpoint);
+        if age >= ttl && !self.config.offline {
+            return None;
+        }
+        let value: Value = serde_json::from_str(&cached.body).ok()?;
+        self.memory_insert(key.to_string(), value.clone(), ttl.saturating_sub(age));
+        Some(value)
+    }
+
+    pub fn insert(&self, key: String, endpoint: &str, body: &str, value: Value) {
+        let ttl = self.config.ttl(endpoint);
+        if ttl.is_zero() {
+            return;
+        }
+        if let Some(storage) = &self.config.storage {
+            let cached = CachedBody {
+                body: body.to_string(),
+                fetched_at: SystemTime::now(),
+            };
+            if storage.put(&key, &cached).is_err() {
+                self.lock().stats.storage_errors += 1;
+            }
+        }
+        self.memory_insert(key, value, ttl);
+    }
+
+    fn memory_insert(&self, key: String, value: Value, ttl: Duration) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        entries.remove(&key);
+        while entries.entries.len() >= self.config.capacity {
+            let oldest = match entries.recency.first_key_value() {
+                Some((_, key)) => key.clone(),
+                None => break,
+            };
+            entries.remove(&oldest);
+            entries.stats.evictions += 1;
+        }
+        entries.entries.insert(
+            key.clone(),
+            Entry {
+                value,
+                expires: Instant::now() + ttl,
+                last_used: 0,
+            },
+        );
+        entries.touch(&key);
+        entries.stats.insertions += 1;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.lock();
+        CacheStats {
+            entries: entries.entries.len(),
+            ..entries.stats
+        }
+    }
+
+    pub fn clear(&self) {
+        {
+            let mut entries = self.lock();
+            entries.entries.clear();
+            entries.recency.clear();
+        }
+        if let Some(storage) = &self.config.storage {
+            if storage.clear().is_err() {
+                self.lock().stats.storage_errors += 1;
+            }
+        }
+    }
+
+    fn invalidate_matching(&self, matches: impl Fn(&str) -> bool) {
+        {
+            let mut entries = self.lock();
+            let keys: Vec<String> = entries
+                .entries
+                .keys()
+                .filter(|key| matches(key))
+                .cloned()
+                .collect();
+            for key in keys {
+                entries.remove(&key);
+            }
+        }
+        // the lock is not held during file io so other requests are not blocked
+        if let Some(storage) = &self.config.storage {
+            let errors = match storage.keys() {
+                Ok(keys) => keys
+                    .iter()
+                    .filter(|key| matches(key))
+                    .filter(|key| storage.remove(key).is_err())
+                    .count(),
+                Err(_) => 1,
+            };
+            if errors > 0 {
+                self.lock().stats.storage_errors += errors as u64;
+            }
+        }
+    }
+
+    pub fn invalidate_endpoint(&self, endpoint: &str) {
+        self.invalidate_matching(|key| key_endpoint(key) == endpoint);
+    }
+
+    // Every page and parameter variant of an api path, e.g. "track/3135556" or "artist/27/top"
+    pub fn invalidate_api_path(&self, path: &str) {
+        let prefix = api_key(path);
+        self.invalidate_matching(|key| {
+            key.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('?'))
+        });
+    }
+}
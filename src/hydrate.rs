@@ -0,0 +1,119 @@
+use std::future::Future;
+
+use crate::models::api;
+use crate::models::ids::{AlbumId, ArtistId, PlaylistId, TrackId};
+use crate::{Deezer, DeezerError};
+
+// Partial models from lists and searches which can be fetched in full, one by
+// one with hydrate or in batches with Deezer::hydrate_all
+pub trait Hydrate {
+    type Full: Send + 'static;
+    // id passed to the endpoint of the full object
+    type Id: Copy + Send + 'static;
+
+    fn full_id(&self) -> Self::Id;
+
+    fn fetch_full(
+        deezer: Deezer,
+        id: Self::Id,
+    ) -> impl Future<Output = Result<Self::Full, DeezerError>> + Send + 'static;
+
+    fn hydrate(
+        &self,
+        deezer: &Deezer,
+    ) -> impl Future<Output = Result<Self::Full, DeezerError>> + Send + 'static {
+        Self::fetch_full(deezer.clone(), self.full_id())
+    }
+}
+
+impl Hydrate for api::Track {
+    type Full = api::MainTrack;
+    type Id = TrackId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        deezer.track(id).await
+    }
+}
+
+impl Hydrate for api::AlternativeTrack {
+    type Full = api::MainTrack;
+    type Id = TrackId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        api::Track::fetch_full(deezer, id).await
+    }
+}
+
+impl Hydrate for api::Album {
+    type Full = api::MainAlbum;
+    type Id = AlbumId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        deezer.album(id).await
+    }
+}
+
+// Artists nested in tracks and albums only have an id, name and tracklist
+impl Hydrate for api::Artist {
+    type Full = api::Artist;
+    type Id = ArtistId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        deezer.artist(id).await
+    }
+}
+
+impl Hydrate for api::Contributor {
+    type Full = api::Artist;
+    type Id = ArtistId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        api::Artist::fetch_full(deezer, id).await
+    }
+}
+
+impl Hydrate for api::RelatedArtist {
+    type Full = api::Artist;
+    type Id = ArtistId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        api::Artist::fetch_full(deezer, id).await
+    }
+}
+
+impl Hydrate for api::Playlist {
+    type Full = api::MainPlaylist;
+    type Id = PlaylistId;
+
+    fn full_id(&self) -> Self::Id {
+        self.id
+    }
+
+    async fn fetch_full(deezer: Deezer, id: Self::Id) -> Result<Self::Full, DeezerError> {
+        deezer.playlist(id).await
+    }
+}
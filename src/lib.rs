@@ -1,35 +1,120 @@
 use async_recursion::async_recursion;
-use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, USER_AGENT};
-use reqwest::{header, Error as RequestError, Url};
-use reqwest::{Client, Response};
+use reqwest::{Error as RequestError, Url};
 use serde::de::DeserializeOwned;
 use serde_json::{json, Error as JsonError, Value};
 use std::collections::HashMap;
+use std::future::Future;
+#[cfg(feature = "tagging")]
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::Mutex;
+use tokio::task::{JoinError, JoinSet};
 use url::ParseError;
 
-#[allow(dead_code)]
+use availability::CountryFilter;
+use cache::{CacheConfig, CacheStats, ResponseCache};
+use hydrate::Hydrate;
+use link::{DeezerResource, LinkError, Resolved};
+use metrics::{Metrics, MetricsSnapshot};
+use models::ids::{
+    AlbumId, ArtistId, GenreId, Isrc, PlaylistId, TrackId, Upc,
+};
+use middleware::Middleware;
+use rate_limit::RateLimiter;
+use single_flight::{ShareError, SingleFlight};
+use trace::{Instrument, Span};
+use transport::{
+    HttpBody, HttpRequest, HttpResponse, ReqwestTransport, Transport,
+    TransportError,
+};
+
+pub mod availability;
 pub mod blocking;
 
+pub mod cache;
+pub mod hydrate;
+pub mod link;
+pub mod lyrics;
+pub mod metrics;
+pub mod middleware;
 pub mod models;
+mod rate_limit;
+mod single_flight;
+mod trace;
+
+#[cfg(feature = "tagging")]
+pub mod library;
+#[cfg(feature = "tagging")]
+pub mod tagging;
+pub mod transport;
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::ptr_arg)]
 mod tests;
 #[cfg(test)]
+mod tests_availability;
+#[cfg(test)]
+mod tests_cache;
+#[cfg(test)]
+mod tests_domain;
+#[cfg(test)]
+mod tests_export;
+#[cfg(test)]
+mod tests_hydrate;
+#[cfg(test)]
+mod tests_ids;
+#[cfg(test)]
+mod tests_language;
+#[cfg(test)]
+mod tests_link;
+#[cfg(test)]
+mod tests_lyrics;
+#[cfg(test)]
+mod tests_metrics;
+#[cfg(test)]
+mod tests_middleware;
+#[cfg(test)]
+mod tests_single_flight;
+#[cfg(test)]
+mod tests_transport;
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::ptr_arg)]
 mod tests_blocking;
 #[cfg(test)]
+mod tests_bulk;
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests_random_blocking;
+#[cfg(all(test, feature = "tagging"))]
+mod tests_library;
+#[cfg(all(test, feature = "tagging"))]
+mod tests_tagging;
+#[cfg(all(test, feature = "tracing"))]
+mod tests_tracing;
 
 const GW_API_URL: &str = "http://www.deezer.com/ajax/gw-light.php";
 const API_URL: &str = "https://api.deezer.com/";
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_LANGUAGE: &str = "en";
+// Ids per song.getListData request, longer lists get rejected
+const GW_SONGS_CHUNK: usize = 200;
+// Short links redirect once or twice, more hops mean a loop
+const MAX_LINK_REDIRECTS: usize = 5;
 const USER_AGENT_HEADER: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/115.0.5790.111 Safari/537.36";
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Deezer {
-    client: Client,
+    transport: Arc<dyn Transport>,
+    middleware: Vec<Arc<dyn Middleware>>,
     token: Arc<Mutex<String>>,
+    cache: Option<Arc<ResponseCache>>,
+    flights: SingleFlight<Value, DeezerError>,
+    metrics: Arc<Metrics>,
+    rate_limiter: RateLimiter,
+    concurrency: usize,
+    language: String,
+    country_filter: Option<CountryFilter>,
 }
 
 #[derive(Debug)]
@@ -38,68 +123,414 @@ pub enum DeezerError {
     RequestError(RequestError),
     ApiError(String),
     ParseError(ParseError),
+    // offline cache mode and the response was never stored
+    NotCached(String),
+    // failures of transports not backed by reqwest
+    TransportError(String),
+    LinkError(LinkError),
 }
 
-async fn parse_response_to_value(
-    response: Response,
-) -> Result<Value, DeezerError> {
-    let body = match response.text().await {
-        Ok(v) => v,
-        Err(err) => return Err(DeezerError::RequestError(err)),
-    };
-    let value: Value = match serde_json::from_str(body.as_str()) {
+impl DeezerError {
+    // Short name of the variant, e.g. for logs and metrics labels
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeezerError::JsonError(_) => "json",
+            DeezerError::RequestError(_) => "request",
+            DeezerError::ApiError(_) => "api",
+            DeezerError::ParseError(_) => "parse",
+            DeezerError::NotCached(_) => "not_cached",
+            DeezerError::TransportError(_) => "transport",
+            DeezerError::LinkError(_) => "link",
+        }
+    }
+}
+
+// reqwest errors can not be copied, their message is kept as a transport error
+impl ShareError for DeezerError {
+    fn share(&self) -> Self {
+        match self {
+            DeezerError::JsonError(err) => {
+                DeezerError::JsonError(serde::de::Error::custom(err))
+            }
+            DeezerError::RequestError(err) => {
+                DeezerError::TransportError(err.to_string())
+            }
+            DeezerError::ApiError(message) => {
+                DeezerError::ApiError(message.clone())
+            }
+            DeezerError::ParseError(err) => DeezerError::ParseError(*err),
+            DeezerError::NotCached(key) => DeezerError::NotCached(key.clone()),
+            DeezerError::TransportError(message) => {
+                DeezerError::TransportError(message.clone())
+            }
+            DeezerError::LinkError(err) => DeezerError::LinkError(err.clone()),
+        }
+    }
+}
+
+impl From<TransportError> for DeezerError {
+    fn from(err: TransportError) -> Self {
+        match err {
+            TransportError::RequestError(err) => DeezerError::RequestError(err),
+            TransportError::Other(err) => DeezerError::TransportError(err),
+        }
+    }
+}
+
+impl From<LinkError> for DeezerError {
+    fn from(err: LinkError) -> Self {
+        DeezerError::LinkError(err)
+    }
+}
+
+// File io of the cache storage runs outside of the async runtime's workers
+async fn blocking<T, F>(f: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(err) => std::panic::resume_unwind(JoinError::into_panic(err)),
+    }
+}
+
+fn parse_body_to_value(body: &str) -> Result<Value, DeezerError> {
+    let value: Value = match serde_json::from_str(body) {
         Ok(v) => v,
         Err(err) => return Err(DeezerError::JsonError(err)),
     };
-    match value.get("error") {
-        Some(error_value) => {
-            if error_value.is_object() {
-                return Err(DeezerError::ApiError(error_value.to_string()));
-            }
+    if let Some(error_value) = value.get("error") {
+        if error_value.is_object() {
+            return Err(DeezerError::ApiError(error_value.to_string()));
         }
-        None => (),
-    };
+    }
     Ok(value)
 }
 
+impl Default for Deezer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Deezer {
     pub fn new() -> Self {
-        let mut headers = header::HeaderMap::new();
-        headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_HEADER));
-        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en"));
-
-        let client = Client::builder()
-            .cookie_store(true)
-            .default_headers(headers)
-            .timeout(Duration::from_secs(15))
-            .build()
-            .expect("Could not establish client!");
-
         Self {
-            client,
+            transport: Arc::new(ReqwestTransport::new()),
+            middleware: Vec::new(),
             token: Arc::new(Mutex::new(String::from("null"))),
+            cache: None,
+            flights: SingleFlight::default(),
+            metrics: Arc::default(),
+            rate_limiter: RateLimiter::default(),
+            language: DEFAULT_LANGUAGE.to_string(),
+            country_filter: None,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    // Every request goes through transport, ReqwestTransport by default
+    pub fn with_transport(
+        mut self,
+        transport: impl Transport + 'static,
+    ) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    // Sent as Accept-Language and as the gw lang parameter, "en" by default
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    // Clone for calls in another language, sharing the cache and transport
+    // e.g. deezer.in_language("fr").genres()
+    pub fn in_language(&self, language: &str) -> Self {
+        self.clone().with_language(language)
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    // Applied to the gw song lists, the only lists telling countries apart
+    pub fn with_country_filter(mut self, filter: CountryFilter) -> Self {
+        self.country_filter = Some(filter);
+        self
+    }
+
+    fn filter_songs(&self, list: &mut models::gw::SongListData) {
+        if let Some(filter) = &self.country_filter {
+            filter.apply(list);
+        }
+    }
+
+    // Shared by clones, counts requests sent through the transport only
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    // Quota usage is kept since requests still count against it
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    // Middleware run in the order they were added
+    pub fn with_middleware(
+        mut self,
+        middleware: impl Middleware + 'static,
+    ) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    // Requests run at once by the bulk methods such as tracks(ids)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    // Clones made after this share the cache
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(config)));
+        self
+    }
+
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    // Distinct requests currently awaited, identical concurrent calls share one
+    pub fn in_flight_requests(&self) -> usize {
+        self.flights.in_flight()
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    pub fn invalidate_cache_endpoint(&self, endpoint: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_endpoint(endpoint);
+        }
+    }
+
+    pub fn invalidate_cache_path(&self, path: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_api_path(path);
+        }
+    }
+
+    async fn cache_get(
+        &self,
+        key: &str,
+        endpoint: &str,
+    ) -> Result<Option<Value>, DeezerError> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
+        let cached = match cache.has_storage() {
+            true => {
+                let (cache, key) = (cache.clone(), key.to_string());
+                let endpoint = endpoint.to_string();
+                blocking(move || cache.get(&key, &endpoint)).await
+            }
+            false => cache.get(key, endpoint),
+        };
+        match cached {
+            Some(value) => Ok(Some(value)),
+            None if cache.is_offline() => {
+                Err(DeezerError::NotCached(key.to_string()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn cache_insert(
+        &self,
+        key: String,
+        endpoint: &str,
+        body: &str,
+        value: &Value,
+    ) {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return,
+        };
+        match cache.has_storage() {
+            true => {
+                let cache = cache.clone();
+                let (endpoint, body) = (endpoint.to_string(), body.to_string());
+                let value = value.clone();
+                blocking(move || cache.insert(key, &endpoint, &body, value))
+                    .await
+            }
+            false => cache.insert(key, endpoint, body, value.clone()),
+        }
+    }
+
+    // Results follow the order of keys, at most self.concurrency run at once
+    // and their api requests wait in send for room in the quota
+    async fn bulk<K, T, F, Fut>(
+        &self,
+        keys: Vec<K>,
+        fetch: F,
+    ) -> Vec<Result<T, DeezerError>>
+    where
+        T: Send + 'static,
+        F: Fn(Deezer, K) -> Fut,
+        Fut: Future<Output = Result<T, DeezerError>> + Send + 'static,
+    {
+        let span = trace::span!(
+            "deezer.bulk",
+            count = keys.len(),
+            concurrency = self.concurrency
+        );
+        let mut results: Vec<Option<Result<T, DeezerError>>> =
+            keys.iter().map(|_| None).collect();
+        let mut tasks = JoinSet::new();
+        let mut store = |joined| match joined {
+            Ok((index, result)) => results[index] = Some(result),
+            Err(err) => std::panic::resume_unwind(JoinError::into_panic(err)),
+        };
+        for (index, key) in keys.into_iter().enumerate() {
+            if tasks.len() >= self.concurrency.max(1) {
+                if let Some(joined) = tasks.join_next().await {
+                    store(joined);
+                }
+            }
+            let task = fetch(self.clone(), key);
+            let task = async move { (index, task.await) };
+            tasks.spawn(task.instrument(span.clone()));
+        }
+        while let Some(joined) = tasks.join_next().await {
+            store(joined);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    // page is the index of the page while following "next" links
+    async fn api_value(
+        &self,
+        url_path: &str,
+        page: Option<usize>,
+    ) -> Result<Value, DeezerError> {
+        let key = cache::api_key(url_path);
+        let key = cache::localized_key(key, &self.language);
+        let endpoint = cache::api_endpoint(url_path);
+        let span = trace::span!(
+            "deezer.api",
+            endpoint,
+            path = url_path,
+            page = trace::Empty,
+            cache_hit = trace::Empty,
+            error = trace::Empty
+        );
+        span.record("page", page);
+        let fetch = || async {
+            let response = self.method_call(url_path).await?;
+            let body = response.text();
+            let value: Value = parse_body_to_value(&body)?;
+            self.cache_insert(key.clone(), endpoint, &body, &value).await;
+            Ok(value)
+        };
+        let result = self
+            .cached_value(&key, endpoint, &span, fetch)
+            .instrument(span.clone())
+            .await;
+        if let Err(err) = &result {
+            span.record("error", err.kind());
+            if let DeezerError::ApiError(_) | DeezerError::JsonError(_) = err {
+                let label = metrics::api_endpoint_label(url_path);
+                self.metrics.record_error(&label, err.kind(), None);
+            }
+        }
+        result
+    }
+
+    // Records cache_hit on span
+    async fn cached_value<F, Fut>(
+        &self,
+        key: &str,
+        endpoint: &str,
+        span: &Span,
+        fetch: F,
+    ) -> Result<Value, DeezerError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Value, DeezerError>>,
+    {
+        let cached = self.cache_get(key, endpoint).await?;
+        span.record("cache_hit", cached.is_some());
+        match cached {
+            Some(value) => Ok(value),
+            None => self.flights.run(key, fetch).await,
+        }
+    }
+
+    async fn gw_value<F, Fut>(
+        &self,
+        method: &str,
+        key: String,
+        send: F,
+    ) -> Result<Value, DeezerError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<HttpResponse, DeezerError>>,
+    {
+        let key = cache::localized_key(key, &self.language);
+        let span = trace::span!(
+            "deezer.gw",
+            method,
+            cache_hit = trace::Empty,
+            error = trace::Empty
+        );
+        let fetch = || async {
+            let response = send().await?;
+            let body = response.text();
+            let value = parse_body_to_value(&body)?;
+            self.cache_insert(key.clone(), method, &body, &value).await;
+            Ok(value)
+        };
+        let result = self
+            .cached_value(&key, method, &span, fetch)
+            .instrument(span.clone())
+            .await;
+        if let Err(err) = &result {
+            span.record("error", err.kind());
+            if let DeezerError::ApiError(_) | DeezerError::JsonError(_) = err {
+                let label = format!("gw:{method}");
+                self.metrics.record_error(&label, err.kind(), None);
+            }
         }
+        result
     }
 
     #[async_recursion]
     pub async fn refresh_token(&self) -> String {
+        let span = trace::span!("deezer.refresh_token");
         let empty_token = String::from("null");
-        let response = self.gw_method_call("deezer.getUserData").await;
+        let response = self
+            .gw_method_call("deezer.getUserData")
+            .instrument(span)
+            .await;
         match response {
-            Ok(resp) => match resp.text().await {
-                Ok(body) => match serde_json::from_str(body.as_str())
-                    as Result<Value, JsonError>
-                {
-                    Ok(value) => match value["results"]["checkForm"].as_str() {
-                        Some(new_token) => {
-                            let new_token = new_token.to_owned();
-                            let mut token = self.token.lock().await;
-                            *token = new_token.clone();
-                            new_token
-                        }
-                        None => empty_token,
-                    },
-                    Err(_) => empty_token,
+            Ok(resp) => match serde_json::from_str(resp.text().as_str())
+                as Result<Value, JsonError>
+            {
+                Ok(value) => match value["results"]["checkForm"].as_str() {
+                    Some(new_token) => {
+                        let new_token = new_token.to_owned();
+                        let mut token = self.token.lock().await;
+                        *token = new_token.clone();
+                        new_token
+                    }
+                    None => empty_token,
                 },
                 Err(_) => empty_token,
             },
@@ -107,7 +538,71 @@ impl Deezer {
         }
     }
 
-    async fn method_call(&self, path: &str) -> Result<Response, DeezerError> {
+    async fn send(
+        &self,
+        mut request: HttpRequest,
+    ) -> Result<HttpResponse, DeezerError> {
+        request
+            .headers
+            .push(("accept-language".to_string(), self.language.clone()));
+        for middleware in &self.middleware {
+            middleware.before_request(&mut request);
+        }
+        // requests are not retried, every request is a first attempt
+        let span = trace::span!(
+            "deezer.http",
+            method = ?request.method,
+            endpoint = request.endpoint(),
+            attempt = 1,
+            status = trace::Empty,
+            error = trace::Empty
+        );
+        let label = metrics::request_endpoint(&request);
+        if let Some(label) = &label {
+            // api requests over the quota are rejected by deezer
+            if label.starts_with("api:") {
+                self.rate_limiter.acquire().instrument(span.clone()).await;
+            }
+            self.metrics.record_sent(label);
+        }
+        let started = Instant::now();
+        let result = self
+            .transport
+            .send(request.clone())
+            .instrument(span.clone())
+            .await;
+        let latency = started.elapsed();
+        match result {
+            Ok(response) => {
+                span.record("status", response.status);
+                if let Some(label) = &label {
+                    let status = response.status;
+                    self.metrics.record_response(label, status, latency);
+                }
+                for middleware in &self.middleware {
+                    middleware.after_response(&request, &response, latency);
+                }
+                Ok(response)
+            }
+            Err(err) => {
+                let err = DeezerError::from(err);
+                span.record("error", err.kind());
+                if let Some(label) = &label {
+                    let kind = err.kind();
+                    self.metrics.record_error(label, kind, Some(latency));
+                }
+                for middleware in &self.middleware {
+                    middleware.on_error(&request, &err, latency);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn method_call(
+        &self,
+        path: &str,
+    ) -> Result<HttpResponse, DeezerError> {
         let mut url = match Url::parse(API_URL) {
             Ok(base_url) => match base_url.join(path) {
                 Ok(url) => url,
@@ -124,20 +619,16 @@ impl Deezer {
             .or_insert("100".to_string());
         url.query_pairs_mut()
             .clear()
-            .extend_pairs(params.into_iter());
-        let response = self.client.get(url).send().await;
-        match response {
-            Ok(r) => Ok(r),
-            Err(error) => Err(DeezerError::RequestError(error)),
-        }
+            .extend_pairs(params);
+        self.send(HttpRequest::get(url)).await
     }
 
     async fn gw_method_call(
         &self,
         method: &str,
-    ) -> Result<Response, RequestError> {
+    ) -> Result<HttpResponse, DeezerError> {
         let mut token = self.token.lock().await.to_owned();
-        if token == "null" && !(method == "deezer.getUserData") {
+        if token == "null" && method != "deezer.getUserData" {
             self.refresh_token().await;
             token = self.token.lock().await.to_owned();
         }
@@ -152,19 +643,20 @@ impl Deezer {
         params.insert("api_version", "1.0".to_owned());
         params.insert("method", method.to_owned());
         params.insert("input", params.len().to_string());
+        params.insert("lang", self.language.clone());
 
-        let response =
-            self.client.post(GW_API_URL).form(&params).send().await?;
-        Ok(response)
+        let url = Url::parse(GW_API_URL).unwrap();
+        self.send(HttpRequest::post(url, transport::form_body(params)))
+            .await
     }
 
     async fn gw_method_call_params(
         &self,
         method: &str,
         params: HashMap<&str, String>,
-    ) -> Result<Response, RequestError> {
+    ) -> Result<HttpResponse, DeezerError> {
         let mut token = self.token.lock().await.to_owned();
-        if token == "null" && !(method == "deezer.getUserData") {
+        if token == "null" && method != "deezer.getUserData" {
             self.refresh_token().await;
             token = self.token.lock().await.to_owned();
         }
@@ -179,19 +671,20 @@ impl Deezer {
         params.insert("api_version", "1.0".to_owned());
         params.insert("method", method.to_owned());
         params.insert("input", params.len().to_string());
+        params.insert("lang", self.language.clone());
 
-        let response =
-            self.client.post(GW_API_URL).form(&params).send().await?;
-        Ok(response)
+        let url = Url::parse(GW_API_URL).unwrap();
+        self.send(HttpRequest::post(url, transport::form_body(params)))
+            .await
     }
 
     async fn gw_method_call_body(
         &self,
         method: &str,
         body: &Value,
-    ) -> Result<Response, RequestError> {
+    ) -> Result<HttpResponse, DeezerError> {
         let mut token = self.token.lock().await.to_owned();
-        if token == "null" && !(method == "deezer.getUserData") {
+        if token == "null" && method != "deezer.getUserData" {
             self.refresh_token().await;
             token = self.token.lock().await.to_owned();
         }
@@ -206,11 +699,12 @@ impl Deezer {
         params.insert("api_version", "1.0".to_owned());
         params.insert("method", method.to_owned());
         params.insert("input", params.len().to_string());
+        params.insert("lang", self.language.clone());
 
         let url = Url::parse_with_params(GW_API_URL, params).unwrap();
 
-        let response = self.client.post(url).json(body).send().await?;
-        Ok(response)
+        self.send(HttpRequest::post(url, HttpBody::Json(body.clone())))
+            .await
     }
 
     async fn call_deserialize_gw_request_with_params<T: DeserializeOwned>(
@@ -218,15 +712,15 @@ impl Deezer {
         method: &str,
         params: HashMap<&str, String>,
     ) -> Result<T, DeezerError> {
-        let response: Response =
-            match self.gw_method_call_params(method, params).await {
-                Ok(r) => r,
-                Err(err) => return Err(DeezerError::RequestError(err)),
-            };
-        let value: Value = parse_response_to_value(response).await?;
+        let key = cache::gw_key(
+            method,
+            params.iter().map(|(k, v)| (*k, v.as_str())),
+        );
+        let send = || self.gw_method_call_params(method, params.clone());
+        let value: Value = self.gw_value(method, key, send).await?;
         match serde_json::from_value(value["results"].clone()) {
             Ok(v) => Ok(v),
-            Err(err) => return Err(DeezerError::JsonError(err)),
+            Err(err) => Err(DeezerError::JsonError(err)),
         }
     }
 
@@ -234,11 +728,10 @@ impl Deezer {
         &self,
         url_path: &str,
     ) -> Result<T, DeezerError> {
-        let response: Response = self.method_call(url_path).await?;
-        let value: Value = parse_response_to_value(response).await?;
+        let value: Value = self.api_value(url_path, None).await?;
         match serde_json::from_value(value) {
             Ok(v) => Ok(v),
-            Err(err) => return Err(DeezerError::JsonError(err)),
+            Err(err) => Err(DeezerError::JsonError(err)),
         }
     }
 
@@ -248,10 +741,8 @@ impl Deezer {
     ) -> Result<Vec<T>, DeezerError> {
         let mut objects: Vec<T> = Vec::new();
         let mut url_path = url_path.to_string();
-        loop {
-            let response: Response =
-                self.method_call(url_path.as_str()).await?;
-            let value: Value = parse_response_to_value(response).await?;
+        for page in 0.. {
+            let value = self.api_value(url_path.as_str(), Some(page)).await?;
             let result: Vec<T> =
                 match serde_json::from_value(value["data"].clone()) {
                     Ok(v) => v,
@@ -270,73 +761,134 @@ impl Deezer {
         Ok(objects)
     }
 
+    // Only the objects of the first page, next is not followed
+    #[cfg(feature = "tagging")]
+    async fn call_deserialize_api_request_first_page<T: DeserializeOwned>(
+        &self,
+        url_path: &str,
+    ) -> Result<Vec<T>, DeezerError> {
+        let value = self.api_value(url_path, Some(0)).await?;
+        match serde_json::from_value(value["data"].clone()) {
+            Ok(v) => Ok(v),
+            Err(err) => Err(DeezerError::JsonError(err)),
+        }
+    }
+
     pub async fn gw_track(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::gw::TrackData, DeezerError> {
+        let span = trace::span!("deezer.gw_track", %song_id);
         let params: HashMap<&str, String> =
             [("sng_id", song_id.to_string())].into();
         self.call_deserialize_gw_request_with_params("deezer.pageTrack", params)
+            .instrument(span)
             .await
     }
 
     pub async fn gw_song(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::gw::Song, DeezerError> {
+        let span = trace::span!("deezer.gw_song", %song_id);
         let params: HashMap<&str, String> =
             [("sng_id", song_id.to_string())].into();
         self.call_deserialize_gw_request_with_params("song.getData", params)
+            .instrument(span)
             .await
     }
 
+    // Large id lists are split in chunks fetched concurrently
     pub async fn gw_songs(
         &self,
-        song_ids: &Vec<u64>,
+        song_ids: &[TrackId],
     ) -> Result<models::gw::SongListData, DeezerError> {
-        let body: Value = json!({"sng_ids": song_ids});
-        let response: Response =
-            match self.gw_method_call_body("song.getListData", &body).await {
-                Ok(r) => r,
-                Err(err) => return Err(DeezerError::RequestError(err)),
-            };
-        let value: Value = parse_response_to_value(response).await?;
+        let span = trace::span!("deezer.gw_songs", count = song_ids.len());
+        let chunks: Vec<Vec<TrackId>> = song_ids
+            .chunks(GW_SONGS_CHUNK)
+            .map(<[TrackId]>::to_vec)
+            .collect();
+        let results = self
+            .bulk(chunks, |deezer, chunk| async move {
+                deezer.gw_songs_chunk(&chunk).await
+            })
+            .instrument(span)
+            .await;
+        let mut parts = Vec::new();
+        let mut error = None;
+        for result in results {
+            match result {
+                Ok(part) => parts.push(part),
+                Err(err) => error = Some(err),
+            }
+        }
+        // the ids of a failed chunk end up in missing_ids, the error is only
+        // returned when no chunk succeeded
+        if let (true, Some(err)) = (parts.is_empty(), error) {
+            return Err(err);
+        }
+        let mut songs = models::gw::SongListData::merge(parts, song_ids);
+        self.filter_songs(&mut songs);
+        Ok(songs)
+    }
+
+    async fn gw_songs_chunk(
+        &self,
+        song_ids: &[TrackId],
+    ) -> Result<models::gw::SongListData, DeezerError> {
+        let ids: Vec<u64> = song_ids.iter().map(|id| id.0).collect();
+        let body: Value = json!({ "sng_ids": ids });
+        let body_key = body.to_string();
+        let key =
+            cache::gw_key("song.getListData", [("body", body_key.as_str())]);
+        let method = "song.getListData";
+        let send = || self.gw_method_call_body(method, &body);
+        let value: Value = self.gw_value(method, key, send).await?;
         match serde_json::from_value(value["results"].clone()) {
             Ok(songs) => Ok(songs),
-            Err(err) => return Err(DeezerError::JsonError(err)),
+            Err(err) => Err(DeezerError::JsonError(err)),
         }
     }
 
     pub async fn gw_songs_from_album(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<models::gw::SongListData, DeezerError> {
+        let span = trace::span!("deezer.gw_songs_from_album", %album_id);
         let params: HashMap<&str, String> =
             [("alb_id", album_id.to_string())].into();
-        self.call_deserialize_gw_request_with_params(
-            "song.getListByAlbum",
-            params,
-        )
-        .await
+        let mut songs = self
+            .call_deserialize_gw_request_with_params(
+                "song.getListByAlbum",
+                params,
+            )
+            .instrument(span)
+            .await?;
+        self.filter_songs(&mut songs);
+        Ok(songs)
     }
 
     pub async fn gw_album(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<models::gw::Album, DeezerError> {
+        let span = trace::span!("deezer.gw_album", %album_id);
         let params: HashMap<&str, String> =
             [("alb_id", album_id.to_string())].into();
         self.call_deserialize_gw_request_with_params("album.getData", params)
+            .instrument(span)
             .await
     }
 
     pub async fn gw_lyrics(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::gw::Lyrics, DeezerError> {
+        let span = trace::span!("deezer.gw_lyrics", %song_id);
         let params: HashMap<&str, String> =
             [("sng_id", song_id.to_string())].into();
         self.call_deserialize_gw_request_with_params("song.getLyrics", params)
+            .instrument(span)
             .await
     }
 
@@ -357,177 +909,285 @@ impl Deezer {
 
     pub async fn track(
         &self,
-        song_id: u64,
+        song_id: TrackId,
     ) -> Result<models::api::MainTrack, DeezerError> {
+        let span = trace::span!("deezer.track", %song_id);
         self.call_deserialize_api_request(format!("track/{}", song_id).as_str())
+            .instrument(span)
             .await
     }
 
+    // Public api, gw and lyrics fetched concurrently, a source failing only
+    // leaves its fields empty unless both the api and gw fail
+    pub async fn track_full(
+        &self,
+        song_id: TrackId,
+    ) -> Result<models::domain::FullTrack, DeezerError> {
+        let span = trace::span!("deezer.track_full", %song_id);
+        async {
+            let (api, gw, lyrics) = tokio::join!(
+                self.track(song_id),
+                self.gw_track(song_id),
+                self.gw_lyrics(song_id)
+            );
+            models::domain::FullTrack::from_parts(api, gw, lyrics)
+        }
+        .instrument(span)
+        .await
+    }
+
     pub async fn track_from_isrc(
         &self,
-        isrc: &str,
+        isrc: &Isrc,
     ) -> Result<models::api::MainTrack, DeezerError> {
+        let span = trace::span!("deezer.track_from_isrc", %isrc);
         self.call_deserialize_api_request(
             format!("track/isrc:{}", isrc).as_str(),
         )
+        .instrument(span)
+        .await
+    }
+
+    pub async fn tracks(
+        &self,
+        song_ids: &[TrackId],
+    ) -> Vec<Result<models::api::MainTrack, DeezerError>> {
+        let span = trace::span!("deezer.tracks", count = song_ids.len());
+        self.bulk(song_ids.to_vec(), |deezer, id| async move {
+            deezer.track(id).await
+        })
+        .instrument(span)
+        .await
+    }
+
+    pub async fn tracks_from_isrcs(
+        &self,
+        isrcs: &[Isrc],
+    ) -> Vec<Result<models::api::MainTrack, DeezerError>> {
+        let span = trace::span!(
+            "deezer.tracks_from_isrcs",
+            count = isrcs.len()
+        );
+        self.bulk(isrcs.to_vec(), |deezer, isrc| async move {
+            deezer.track_from_isrc(&isrc).await
+        })
+        .instrument(span)
         .await
     }
 
     pub async fn album(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<models::api::MainAlbum, DeezerError> {
+        let span = trace::span!("deezer.album", %album_id);
         self.call_deserialize_api_request(
             format!("album/{}", album_id).as_str(),
         )
+        .instrument(span)
+        .await
+    }
+
+    pub async fn albums(
+        &self,
+        album_ids: &[AlbumId],
+    ) -> Vec<Result<models::api::MainAlbum, DeezerError>> {
+        let span = trace::span!("deezer.albums", count = album_ids.len());
+        self.bulk(album_ids.to_vec(), |deezer, id| async move {
+            deezer.album(id).await
+        })
+        .instrument(span)
         .await
     }
 
     pub async fn album_from_upc(
         &self,
-        upc: u64,
+        upc: &Upc,
     ) -> Result<models::api::MainAlbum, DeezerError> {
+        let span = trace::span!("deezer.album_from_upc", %upc);
         self.call_deserialize_api_request(format!("album/upc:{}", upc).as_str())
+            .instrument(span)
             .await
     }
 
     pub async fn album_tracks(
         &self,
-        album_id: u64,
+        album_id: AlbumId,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
+        let span = trace::span!("deezer.album_tracks", %album_id);
         self.call_deserialize_api_request_as_vec(
             format!("album/{}/tracks", album_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn artist(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<models::api::Artist, DeezerError> {
+        let span = trace::span!("deezer.artist", %artist_id);
         self.call_deserialize_api_request(
             format!("artist/{}", artist_id).as_str(),
         )
+        .instrument(span)
+        .await
+    }
+
+    pub async fn artists(
+        &self,
+        artist_ids: &[ArtistId],
+    ) -> Vec<Result<models::api::Artist, DeezerError>> {
+        let span = trace::span!("deezer.artists", count = artist_ids.len());
+        self.bulk(artist_ids.to_vec(), |deezer, id| async move {
+            deezer.artist(id).await
+        })
+        .instrument(span)
         .await
     }
 
     pub async fn artist_albums(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Album>, DeezerError> {
+        let span = trace::span!("deezer.artist_albums", %artist_id);
         self.call_deserialize_api_request_as_vec(
             format!("artist/{}/albums", artist_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn artist_top_tracks(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
+        let span = trace::span!("deezer.artist_top_tracks", %artist_id);
         self.call_deserialize_api_request_as_vec(
             format!("artist/{}/top", artist_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn artist_related_artists(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::RelatedArtist>, DeezerError> {
+        let span = trace::span!("deezer.artist_related_artists", %artist_id);
         self.call_deserialize_api_request_as_vec(
             format!("artist/{}/related", artist_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn artist_radio(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
+        let span = trace::span!("deezer.artist_radio", %artist_id);
         self.call_deserialize_api_request_as_vec(
             format!("artist/{}/radio", artist_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn artist_playlists(
         &self,
-        artist_id: u64,
+        artist_id: ArtistId,
     ) -> Result<Vec<models::api::Playlist>, DeezerError> {
+        let span = trace::span!("deezer.artist_playlists", %artist_id);
         self.call_deserialize_api_request_as_vec(
             format!("artist/{}/playlists", artist_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn editorial(
         &self,
     ) -> Result<Vec<models::api::Editorial>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("editorial").await
+        let span = trace::span!("deezer.editorial");
+        self.call_deserialize_api_request_as_vec("editorial")
+            .instrument(span)
+            .await
     }
 
     pub async fn editorial_from_genre(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<models::api::Editorial, DeezerError> {
+        let span = trace::span!("deezer.editorial_from_genre", %genre_id);
         self.call_deserialize_api_request(
             format!("editorial/{}", genre_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn genres(
         &self,
     ) -> Result<Vec<models::api::Editorial>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("genre").await
+        let span = trace::span!("deezer.genres");
+        self.call_deserialize_api_request_as_vec("genre").instrument(span).await
     }
 
     pub async fn genre(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<models::api::Editorial, DeezerError> {
+        let span = trace::span!("deezer.genre", %genre_id);
         self.call_deserialize_api_request(
             format!("genre/{}", genre_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn genre_artists(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<Vec<models::api::Artist>, DeezerError> {
+        let span = trace::span!("deezer.genre_artists", %genre_id);
         self.call_deserialize_api_request_as_vec(
             format!("genre/{}/artists", genre_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn genre_radios(
         &self,
-        genre_id: u64,
+        genre_id: GenreId,
     ) -> Result<Vec<models::api::Radio>, DeezerError> {
+        let span = trace::span!("deezer.genre_radios", %genre_id);
         self.call_deserialize_api_request_as_vec(
             format!("genre/{}/radios", genre_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn infos(&self) -> Result<models::api::Info, DeezerError> {
-        self.call_deserialize_api_request("infos").await
+        let span = trace::span!("deezer.infos");
+        self.call_deserialize_api_request("infos").instrument(span).await
     }
 
     pub async fn radios(&self) -> Result<Vec<models::api::Radio>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("radio").await
+        let span = trace::span!("deezer.radios");
+        self.call_deserialize_api_request_as_vec("radio").instrument(span).await
     }
 
     pub async fn radio(
         &self,
         radio_id: u64,
     ) -> Result<models::api::Radio, DeezerError> {
+        let span = trace::span!("deezer.radio", radio_id);
         self.call_deserialize_api_request(
             format!("radio/{}", radio_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
@@ -535,37 +1195,195 @@ impl Deezer {
         &self,
         radio_id: u64,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
+        let span = trace::span!("deezer.radio_tracks", radio_id);
         self.call_deserialize_api_request_as_vec(
             format!("radio/{}/tracks", radio_id).as_str(),
         )
+        .instrument(span)
         .await
     }
 
     pub async fn radio_genres(
         &self,
     ) -> Result<Vec<models::api::GenreRadios>, DeezerError> {
+        let span = trace::span!("deezer.radio_genres");
         self.call_deserialize_api_request_as_vec("radio/genres")
+            .instrument(span)
             .await
     }
 
     pub async fn radio_top(
         &self,
     ) -> Result<Vec<models::api::Radio>, DeezerError> {
-        self.call_deserialize_api_request_as_vec("radio/top").await
+        let span = trace::span!("deezer.radio_top");
+        self.call_deserialize_api_request_as_vec("radio/top")
+            .instrument(span)
+            .await
     }
 
     pub async fn radio_lists(
         &self,
     ) -> Result<Vec<models::api::Radio>, DeezerError> {
+        let span = trace::span!("deezer.radio_lists");
         self.call_deserialize_api_request_as_vec("radio/lists")
+            .instrument(span)
+            .await
+    }
+
+    pub async fn playlist(
+        &self,
+        playlist_id: PlaylistId,
+    ) -> Result<models::api::MainPlaylist, DeezerError> {
+        let span = trace::span!("deezer.playlist", %playlist_id);
+        self.call_deserialize_api_request(
+            format!("playlist/{}", playlist_id).as_str(),
+        )
+        .instrument(span)
+        .await
+    }
+
+    pub async fn user(
+        &self,
+        user_id: u64,
+    ) -> Result<models::api::User, DeezerError> {
+        let span = trace::span!("deezer.user", user_id);
+        self.call_deserialize_api_request(format!("user/{}", user_id).as_str())
+            .instrument(span)
             .await
     }
 
+    pub async fn podcast(
+        &self,
+        podcast_id: u64,
+    ) -> Result<models::api::Podcast, DeezerError> {
+        let span = trace::span!("deezer.podcast", podcast_id);
+        self.call_deserialize_api_request(
+            format!("podcast/{}", podcast_id).as_str(),
+        )
+        .instrument(span)
+        .await
+    }
+
+    pub async fn episode(
+        &self,
+        episode_id: u64,
+    ) -> Result<models::api::Episode, DeezerError> {
+        let span = trace::span!("deezer.episode", episode_id);
+        self.call_deserialize_api_request(
+            format!("episode/{}", episode_id).as_str(),
+        )
+        .instrument(span)
+        .await
+    }
+
+    // Follows the tracklist url of an artist, album, radio or playlist model
+    pub async fn tracklist(
+        &self,
+        url: &str,
+    ) -> Result<Vec<models::api::Track>, DeezerError> {
+        let span = trace::span!("deezer.tracklist", url);
+        let path = link::api_url_path(url)?;
+        self.call_deserialize_api_request_as_vec(&path).instrument(span).await
+    }
+
+    pub async fn hydrate<T: Hydrate>(
+        &self,
+        item: &T,
+    ) -> Result<T::Full, DeezerError> {
+        let span = trace::span!("deezer.hydrate");
+        item.hydrate(self).instrument(span).await
+    }
+
+    // Fetched concurrently, results follow the order of items
+    pub async fn hydrate_all<T: Hydrate>(
+        &self,
+        items: &[T],
+    ) -> Vec<Result<T::Full, DeezerError>> {
+        let span = trace::span!("deezer.hydrate_all", count = items.len());
+        let ids = items.iter().map(Hydrate::full_id).collect();
+        self.bulk(ids, T::fetch_full).instrument(span).await
+    }
+
+    // Parses the link, short links are followed through the transport first
+    pub async fn resolve_link(
+        &self,
+        url: &str,
+    ) -> Result<DeezerResource, DeezerError> {
+        let span = trace::span!("deezer.resolve_link", url);
+        async {
+            let mut url = url.trim().to_string();
+            for _ in 0..=MAX_LINK_REDIRECTS {
+                match link::parse(&url) {
+                    Err(LinkError::ShortLink(_)) => {}
+                    result => return Ok(result?),
+                }
+                let request_url = match url.contains("://") {
+                    true => Url::parse(&url),
+                    false => Url::parse(&format!("https://{url}")),
+                };
+                let request_url = request_url.map_err(DeezerError::ParseError)?;
+                let request = HttpRequest::get(request_url.clone());
+                let response = self.send(request).await?;
+                // reqwest follows redirects itself and reports the final url
+                url = match (response.header("location"), &response.url) {
+                    (Some(location), _) => request_url
+                        .join(location)
+                        .map_err(DeezerError::ParseError)?
+                        .to_string(),
+                    (None, Some(final_url)) if *final_url != url => {
+                        final_url.clone()
+                    }
+                    _ => return Err(LinkError::ShortLink(url).into()),
+                };
+            }
+            Err(LinkError::TooManyRedirects(url).into())
+        }
+        .instrument(span)
+        .await
+    }
+
+    // Fetches the model behind a track, album, playlist... link
+    pub async fn resolve(&self, url: &str) -> Result<Resolved, DeezerError> {
+        let span = trace::span!("deezer.resolve", url);
+        async {
+            let resolved = match self.resolve_link(url).await? {
+                DeezerResource::Track(id) => {
+                    Resolved::Track(self.track(id).await?)
+                }
+                DeezerResource::Album(id) => {
+                    Resolved::Album(self.album(id).await?)
+                }
+                DeezerResource::Artist(id) => {
+                    Resolved::Artist(self.artist(id).await?)
+                }
+                DeezerResource::Playlist(id) => {
+                    Resolved::Playlist(self.playlist(id).await?)
+                }
+                DeezerResource::User(id) => {
+                    Resolved::User(self.user(id).await?)
+                }
+                DeezerResource::Podcast(id) => {
+                    Resolved::Podcast(self.podcast(id).await?)
+                }
+                DeezerResource::Episode(id) => {
+                    Resolved::Episode(self.episode(id).await?)
+                }
+                DeezerResource::Radio(id) => {
+                    Resolved::Radio(self.radio(id).await?)
+                }
+            };
+            Ok(resolved)
+        }
+        .instrument(span)
+        .await
+    }
+
     pub async fn search(
         &self,
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Track>, DeezerError> {
+        let span = trace::span!("deezer.search", query, strict);
         let strict = match strict {
             true => "on",
             false => "off",
@@ -573,6 +1391,7 @@ impl Deezer {
         self.call_deserialize_api_request_as_vec(
             format!("search?q={query}&strict={strict}").as_str(),
         )
+        .instrument(span)
         .await
     }
 
@@ -581,6 +1400,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Album>, DeezerError> {
+        let span = trace::span!("deezer.search_album", query, strict);
         let strict = match strict {
             true => "on",
             false => "off",
@@ -588,6 +1408,7 @@ impl Deezer {
         self.call_deserialize_api_request_as_vec(
             format!("search/album?q={query}&strict={strict}").as_str(),
         )
+        .instrument(span)
         .await
     }
 
@@ -596,6 +1417,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Artist>, DeezerError> {
+        let span = trace::span!("deezer.search_artist", query, strict);
         let strict = match strict {
             true => "on",
             false => "off",
@@ -603,6 +1425,7 @@ impl Deezer {
         self.call_deserialize_api_request_as_vec(
             format!("search/artist?q={query}&strict={strict}").as_str(),
         )
+        .instrument(span)
         .await
     }
 
@@ -611,6 +1434,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::Playlist>, DeezerError> {
+        let span = trace::span!("deezer.search_playlist", query, strict);
         let strict = match strict {
             true => "on",
             false => "off",
@@ -618,6 +1442,7 @@ impl Deezer {
         self.call_deserialize_api_request_as_vec(
             format!("search/playlist?q={query}&strict={strict}").as_str(),
         )
+        .instrument(span)
         .await
     }
 
@@ -626,6 +1451,7 @@ impl Deezer {
         query: &str,
         strict: bool,
     ) -> Result<Vec<models::api::User>, DeezerError> {
+        let span = trace::span!("deezer.search_user", query, strict);
         let strict = match strict {
             true => "on",
             false => "off",
@@ -633,6 +1459,7 @@ impl Deezer {
         self.call_deserialize_api_request_as_vec(
             format!("search/user?q={query}&strict={strict}").as_str(),
         )
+        .instrument(span)
         .await
     }
 
@@ -643,30 +1470,167 @@ impl Deezer {
         album: &str,
         strict: bool,
     ) -> Result<models::api::Track, DeezerError> {
-        let strict = match strict {
-            true => "on",
-            false => "off",
+        let span = trace::span!(
+            "deezer.search_track",
+            track,
+            artist,
+            album,
+            strict
+        );
+        async {
+            let strict = match strict {
+                true => "on",
+                false => "off",
+            };
+            let searches: Vec<models::api::Track> = self.call_deserialize_api_request_as_vec(
+                format!(r#"search/track?q=track:"{track}" artist:"{artist}" album:"{album}"&strict={strict}"#).as_str(),
+            ).await?;
+            if let Some(track) = searches.first() {
+                return Ok(track.to_owned());
+            }
+            let searches: Vec<models::api::Track> =
+                self.call_deserialize_api_request_as_vec(format!(r#"search/track?q=track:"{track}" artist:"{artist}"&strict={strict}"#).as_str()).await?;
+            if let Some(track) = searches.first() {
+                return Ok(track.to_owned());
+            }
+            let searches: Vec<models::api::Track> = self
+                .call_deserialize_api_request_as_vec(
+                    format!(r#"search/track?q=track:"{track}"&strict={strict}"#)
+                        .as_str(),
+                )
+                .await?;
+            if let Some(track) = searches.first() {
+                return Ok(track.to_owned());
+            }
+            Err(DeezerError::ApiError("No Track Found".to_string()))
+        }
+        .instrument(span)
+        .await
+    }
+
+    #[cfg(feature = "tagging")]
+    pub async fn cover_art(
+        &self,
+        url: &str,
+    ) -> Result<tagging::Picture, DeezerError> {
+        let span = trace::span!("deezer.cover_art", url);
+        let url = match Url::parse(url) {
+            Ok(url) => url,
+            Err(err) => return Err(DeezerError::ParseError(err)),
         };
-        let searches: Vec<models::api::Track> = self.call_deserialize_api_request_as_vec(
-            format!(r#"search/track?q=track:"{track}" artist:"{artist}" album:"{album}"&strict={strict}"#).as_str(),
-        ).await?;
-        if let Some(_) = searches.get(0) {
-            return Ok(searches[0].to_owned());
-        }
-        let searches: Vec<models::api::Track> =
-            self.call_deserialize_api_request_as_vec(format!(r#"search/track?q=track:"{track}" artist:"{artist}"&strict={strict}"#).as_str()).await?;
-        if let Some(_) = searches.get(0) {
-            return Ok(searches[0].to_owned());
-        }
-        let searches: Vec<models::api::Track> = self
-            .call_deserialize_api_request_as_vec(
-                format!(r#"search/track?q=track:"{track}"&strict={strict}"#)
-                    .as_str(),
-            )
-            .await?;
-        if let Some(_) = searches.get(0) {
-            return Ok(searches[0].to_owned());
+        let response = self.send(HttpRequest::get(url)).instrument(span).await?;
+        if !response.is_success() {
+            return Err(DeezerError::ApiError(format!(
+                "HTTP status {} for cover art",
+                response.status
+            )));
         }
-        Err(DeezerError::ApiError("No Track Found".to_string()))
+        let mime_type = response
+            .header("content-type")
+            .unwrap_or("image/jpeg")
+            .to_string();
+        Ok(tagging::Picture {
+            mime_type,
+            data: response.body,
+        })
+    }
+
+    #[cfg(feature = "tagging")]
+    pub async fn match_local_track(
+        &self,
+        local: &library::LocalTrack,
+    ) -> library::FileMatch {
+        let span = trace::span!("deezer.match_local_track", path = ?local.path);
+        async {
+            // tags may hold codes which are not isrcs, those are searched
+            if let Some(Ok(isrc)) = local.isrc.as_deref().map(Isrc::new) {
+                match self.track_from_isrc(&isrc).await {
+                    Ok(track) => {
+                        return library::FileMatch::from_isrc(local, &track)
+                    }
+                    // deezer does not know the isrc
+                    Err(DeezerError::ApiError(_)) => {}
+                    Err(err) => {
+                        return library::FileMatch::failed(
+                            local,
+                            format!("{err:?}"),
+                        )
+                    }
+                }
+            }
+            let queries = [local.search_query(), local.loose_search_query()];
+            let mut file_match = library::FileMatch::from_search(local, &[]);
+            for query in queries.into_iter().flatten() {
+                // the best candidates are on the first page, a library scan
+                // would otherwise fetch every page of every search
+                let path = format!(
+                    "search?q={query}&strict=off&limit={}",
+                    library::SEARCH_LIMIT
+                );
+                let results = self
+                    .call_deserialize_api_request_first_page(&path)
+                    .await;
+                file_match = match results {
+                    Ok(results) => {
+                        library::FileMatch::from_search(local, &results)
+                    }
+                    Err(err) => {
+                        return library::FileMatch::failed(
+                            local,
+                            format!("{err:?}"),
+                        )
+                    }
+                };
+                if !file_match.candidates.is_empty() {
+                    break;
+                }
+            }
+            file_match
+        }
+        .instrument(span)
+        .await
+    }
+
+    #[cfg(feature = "tagging")]
+    pub async fn match_library(
+        &self,
+        dir: impl AsRef<Path>,
+        report_path: impl AsRef<Path>,
+    ) -> Result<library::MatchReport, library::LibraryError> {
+        let span = trace::span!(
+            "deezer.match_library",
+            dir = ?dir.as_ref(),
+            report_path = ?report_path.as_ref()
+        );
+        async {
+            let report_path = report_path.as_ref();
+            let mut report = library::MatchReport::load(report_path)?;
+            let mut unsaved = 0;
+            for path in library::audio_files(dir)? {
+                if report.is_done(&path) {
+                    continue;
+                }
+                let file_match = match tagging::read_tags(&path) {
+                    Ok(tags) => {
+                        let local = library::LocalTrack::new(path, &tags);
+                        self.match_local_track(&local).await
+                    }
+                    Err(err) => library::FileMatch::failed(
+                        &library::LocalTrack::new(path, &Default::default()),
+                        format!("{err:?}"),
+                    ),
+                };
+                report.insert(file_match);
+                unsaved += 1;
+                if unsaved == library::REPORT_SAVE_INTERVAL {
+                    report.save(report_path)?;
+                    unsaved = 0;
+                }
+            }
+            report.save(report_path)?;
+            Ok(report)
+        }
+        .instrument(span)
+        .await
     }
 }
@@ -0,0 +1,405 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Error as JsonError;
+
+use crate::models;
+use crate::models::ids::TrackId;
+use crate::tagging::{AudioFormat, TagSet};
+
+// Score from which a search result is accepted, and how far ahead of the runner up it must be
+const MATCH_SCORE: f32 = 0.6;
+const HIGH_SCORE: f32 = 0.85;
+const AMBIGUITY_MARGIN: f32 = 0.05;
+// Seconds of duration difference tolerated before the duration score starts dropping
+const DURATION_TOLERANCE: f32 = 2.0;
+const DURATION_LIMIT: f32 = 15.0;
+// Search results requested per query, only the first page is scored
+pub(crate) const SEARCH_LIMIT: usize = 10;
+// match_library saves the report after this many files, and once at the end
+pub(crate) const REPORT_SAVE_INTERVAL: usize = 25;
+
+#[derive(Debug)]
+pub enum LibraryError {
+    IoError(IoError),
+    JsonError(JsonError),
+}
+
+impl From<IoError> for LibraryError {
+    fn from(error: IoError) -> Self {
+        LibraryError::IoError(error)
+    }
+}
+
+impl From<JsonError> for LibraryError {
+    fn from(error: JsonError) -> Self {
+        LibraryError::JsonError(error)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalTrack {
+    pub path: PathBuf,
+    pub isrc: Option<String>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<u64>,
+}
+
+impl LocalTrack {
+    pub fn new(path: impl Into<PathBuf>, tags: &TagSet) -> Self {
+        Self {
+            path: path.into(),
+            isrc: tags.isrc.clone(),
+            title: tags.title.clone(),
+            artist: tags.artists.first().or(tags.album_artist.as_ref()).cloned(),
+            album: tags.album.clone(),
+            duration: tags.duration,
+        }
+    }
+
+    // Advanced search query, None when the file has no title to search for
+    pub fn search_query(&self) -> Option<String> {
+        let title = query_value(self.title.as_deref()?);
+        Some(match &self.artist {
+            Some(artist) => format!(r#"track:"{title}" artist:"{}""#, query_value(artist)),
+            None => format!(r#"track:"{title}""#),
+        })
+    }
+
+    pub fn loose_search_query(&self) -> Option<String> {
+        let title = query_value(self.title.as_deref()?);
+        Some(match &self.artist {
+            Some(artist) => format!("{} {title}", query_value(artist)),
+            None => title,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchStatus {
+    Matched,
+    Ambiguous,
+    Unmatched,
+    // unreadable file or request error, retried on the next run
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchMethod {
+    Isrc,
+    Search,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    pub track_id: TrackId,
+    pub title: String,
+    pub title_short: String,
+    pub artist: String,
+    pub album: String,
+    pub duration: u64,
+    pub score: f32,
+}
+
+impl From<&models::api::Track> for Candidate {
+    fn from(track: &models::api::Track) -> Self {
+        Self {
+            track_id: track.id,
+            title: track.title.clone(),
+            title_short: track.title_short.clone(),
+            artist: track.artist.name.clone(),
+            album: track.album.title.clone(),
+            duration: track.duration,
+            score: 0.0,
+        }
+    }
+}
+
+impl From<&models::api::MainTrack> for Candidate {
+    fn from(track: &models::api::MainTrack) -> Self {
+        Self {
+            track_id: track.id,
+            title: track.title.clone(),
+            title_short: track.title_short.clone(),
+            artist: track.artist.name.clone(),
+            album: track.album.title.clone(),
+            duration: track.duration,
+            score: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileMatch {
+    pub local: LocalTrack,
+    pub status: MatchStatus,
+    pub confidence: Option<Confidence>,
+    pub method: Option<MatchMethod>,
+    pub track_id: Option<TrackId>,
+    // best first, the accepted candidate included
+    pub candidates: Vec<Candidate>,
+    pub error: Option<String>,
+}
+
+// The query goes into the url path, characters which would end the query or the value are dropped
+fn query_value(value: &str) -> String {
+    value
+        .replace(['"', '&', '#', '?', '+'], " ")
+        .trim()
+        .to_string()
+}
+
+fn words(value: &str) -> HashSet<String> {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (words(a), words(b));
+    match a.len().max(b.len()) {
+        0 => 0.0,
+        len => a.intersection(&b).count() as f32 / len as f32,
+    }
+}
+
+fn duration_score(a: u64, b: u64) -> f32 {
+    let difference = (a as f32 - b as f32).abs();
+    let over = (difference - DURATION_TOLERANCE).max(0.0);
+    (1.0 - over / (DURATION_LIMIT - DURATION_TOLERANCE)).max(0.0)
+}
+
+// Weighted similarity between 0 and 1, fields missing from the file are left out
+pub fn score(local: &LocalTrack, candidate: &Candidate) -> f32 {
+    let mut parts: Vec<(f32, f32)> = Vec::new();
+    if let Some(title) = &local.title {
+        let title_score =
+            similarity(title, &candidate.title).max(similarity(title, &candidate.title_short));
+        parts.push((0.5, title_score));
+    }
+    if let Some(artist) = &local.artist {
+        parts.push((0.25, similarity(artist, &candidate.artist)));
+    }
+    if let Some(album) = &local.album {
+        parts.push((0.1, similarity(album, &candidate.album)));
+    }
+    if let Some(duration) = local.duration {
+        parts.push((0.15, duration_score(duration, candidate.duration)));
+    }
+    let weights: f32 = parts.iter().map(|(weight, _)| weight).sum();
+    if weights == 0.0 {
+        return 0.0;
+    }
+    parts.iter().map(|(weight, s)| weight * s).sum::<f32>() / weights
+}
+
+impl FileMatch {
+    // ISRCs are sometimes reused across releases, a title mismatch lowers the confidence
+    pub fn from_isrc(local: &LocalTrack, track: &models::api::MainTrack) -> Self {
+        let mut candidate = Candidate::from(track);
+        candidate.score = score(local, &candidate);
+        let confidence = match candidate.score >= MATCH_SCORE || local.title.is_none() {
+            true => Confidence::High,
+            false => Confidence::Medium,
+        };
+        Self {
+            local: local.clone(),
+            status: MatchStatus::Matched,
+            confidence: Some(confidence),
+            method: Some(MatchMethod::Isrc),
+            track_id: Some(track.id),
+            candidates: vec![candidate],
+            error: None,
+        }
+    }
+
+    pub fn from_search(local: &LocalTrack, results: &[models::api::Track]) -> Self {
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for track in results {
+            if candidates.iter().any(|c| c.track_id == track.id) {
+                continue;
+            }
+            let mut candidate = Candidate::from(track);
+            candidate.score = score(local, &candidate);
+            candidates.push(candidate);
+        }
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let best = candidates.first().map(|c| c.score).unwrap_or(0.0);
+        let close = candidates
+            .iter()
+            .filter(|c| best - c.score <= AMBIGUITY_MARGIN)
+            .count();
+        let (status, confidence) = match best {
+            s if s < MATCH_SCORE => (MatchStatus::Unmatched, Confidence::Low),
+            _ if close > 1 => (MatchStatus::Ambiguous, Confidence::Low),
+            s if s >= HIGH_SCORE => (MatchStatus::Matched, Confidence::High),
+            _ => (MatchStatus::Matched, Confidence::Medium),
+        };
+        Self {
+            local: local.clone(),
+            status,
+            confidence: Some(confidence).filter(|_| !candidates.is_empty()),
+            method: Some(MatchMethod::Search),
+            track_id: candidates
+                .first()
+                .map(|c| c.track_id)
+                .filter(|_| status == MatchStatus::Matched),
+            candidates,
+            error: None,
+        }
+    }
+
+    pub fn failed(local: &LocalTrack, error: String) -> Self {
+        Self {
+            local: local.clone(),
+            status: MatchStatus::Failed,
+            confidence: None,
+            method: None,
+            track_id: None,
+            candidates: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatchReport {
+    pub files: Vec<FileMatch>,
+    // position of each path in files, rebuilt when files was changed directly
+    #[serde(skip)]
+    index: HashMap<PathBuf, usize>,
+}
+
+impl PartialEq for MatchReport {
+    fn eq(&self, other: &Self) -> bool {
+        self.files == other.files
+    }
+}
+
+impl MatchReport {
+    // A missing report file starts an empty report
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LibraryError> {
+        match fs::read(path) {
+            Ok(data) => {
+                let mut report: Self = serde_json::from_slice(&data)?;
+                report.reindex();
+                Ok(report)
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(LibraryError::IoError(err)),
+        }
+    }
+
+    // Written to a temporary file first so an interrupted run never leaves a truncated report
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LibraryError> {
+        let path = path.as_ref();
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(temp, path)?;
+        Ok(())
+    }
+
+    fn reindex(&mut self) {
+        self.index = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.local.path.clone(), i))
+            .collect();
+    }
+
+    fn is_indexed(&self) -> bool {
+        self.index.len() == self.files.len()
+    }
+
+    fn position(&self, path: &Path) -> Option<usize> {
+        match self.index.get(path) {
+            Some(&i) if self.files.get(i).is_some_and(|m| m.local.path == path) => Some(i),
+            None if self.is_indexed() => None,
+            _ => self.files.iter().position(|m| m.local.path == path),
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&FileMatch> {
+        self.position(path).map(|i| &self.files[i])
+    }
+
+    // Failed files are not done so they are retried
+    pub fn is_done(&self, path: &Path) -> bool {
+        self.get(path)
+            .is_some_and(|m| m.status != MatchStatus::Failed)
+    }
+
+    pub fn insert(&mut self, file_match: FileMatch) {
+        if !self.is_indexed() {
+            self.reindex();
+        }
+        match self.position(&file_match.local.path) {
+            Some(i) => self.files[i] = file_match,
+            None => {
+                let path = file_match.local.path.clone();
+                self.index.insert(path, self.files.len());
+                self.files.push(file_match);
+            }
+        }
+    }
+
+    pub fn with_status(&self, status: MatchStatus) -> impl Iterator<Item = &FileMatch> {
+        self.files.iter().filter(move |m| m.status == status)
+    }
+
+    pub fn matched(&self) -> impl Iterator<Item = &FileMatch> {
+        self.with_status(MatchStatus::Matched)
+    }
+
+    pub fn ambiguous(&self) -> impl Iterator<Item = &FileMatch> {
+        self.with_status(MatchStatus::Ambiguous)
+    }
+
+    pub fn unmatched(&self) -> impl Iterator<Item = &FileMatch> {
+        self.with_status(MatchStatus::Unmatched)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &FileMatch> {
+        self.with_status(MatchStatus::Failed)
+    }
+}
+
+// Audio files supported by the tagging module, recursively and in a stable order
+pub fn audio_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, LibraryError> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.as_ref().to_path_buf()];
+    // symlinked directories are followed once, a link back to a parent would
+    // otherwise be walked forever
+    let mut visited = HashSet::new();
+    while let Some(dir) = dirs.pop() {
+        if !visited.insert(fs::canonicalize(&dir)?) {
+            continue;
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if AudioFormat::from_path(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
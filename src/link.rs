@@ -0,0 +1,191 @@
+use reqwest::Url;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::api;
+use crate::models::ids::{AlbumId, ArtistId, PlaylistId, TrackId};
+use crate::API_URL;
+
+// Hosts of the short links shared from the apps, they redirect to a deezer.com url
+const SHORT_LINK_HOSTS: [&str; 2] = ["deezer.page.link", "link.deezer.com"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    InvalidUrl(String),
+    UnsupportedHost(String),
+    UnknownResource(String),
+    // needs to be followed, see Deezer::resolve
+    ShortLink(String),
+    TooManyRedirects(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeezerResource {
+    Track(TrackId),
+    Album(AlbumId),
+    Artist(ArtistId),
+    Playlist(PlaylistId),
+    User(u64),
+    Podcast(u64),
+    Episode(u64),
+    Radio(u64),
+}
+
+impl DeezerResource {
+    pub fn id(&self) -> u64 {
+        match self {
+            DeezerResource::Track(TrackId(id))
+            | DeezerResource::Album(AlbumId(id))
+            | DeezerResource::Artist(ArtistId(id))
+            | DeezerResource::Playlist(PlaylistId(id))
+            | DeezerResource::User(id)
+            | DeezerResource::Podcast(id)
+            | DeezerResource::Episode(id)
+            | DeezerResource::Radio(id) => *id,
+        }
+    }
+
+    // Path of the resource on the public api, e.g. "album/302127"
+    pub fn api_path(&self) -> String {
+        let kind = match self {
+            DeezerResource::Track(_) => "track",
+            DeezerResource::Album(_) => "album",
+            DeezerResource::Artist(_) => "artist",
+            DeezerResource::Playlist(_) => "playlist",
+            DeezerResource::User(_) => "user",
+            DeezerResource::Podcast(_) => "podcast",
+            DeezerResource::Episode(_) => "episode",
+            DeezerResource::Radio(_) => "radio",
+        };
+        format!("{kind}/{}", self.id())
+    }
+
+    // Link to the resource on the website
+    pub fn url(&self) -> String {
+        let path = match self {
+            DeezerResource::User(id) => format!("profile/{id}"),
+            DeezerResource::Podcast(id) => format!("show/{id}"),
+            _ => self.api_path(),
+        };
+        format!("https://www.deezer.com/{path}")
+    }
+}
+
+impl FromStr for DeezerResource {
+    type Err = LinkError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        parse(url)
+    }
+}
+
+impl fmt::Display for DeezerResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url())
+    }
+}
+
+// Model behind a link, fetched by Deezer::resolve. Returned once per call,
+// so the variants are not boxed
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum Resolved {
+    Track(api::MainTrack),
+    Album(api::MainAlbum),
+    Artist(api::Artist),
+    Playlist(api::MainPlaylist),
+    User(api::User),
+    Podcast(api::Podcast),
+    Episode(api::Episode),
+    Radio(api::Radio),
+}
+
+fn resource(kind: &str, id: u64) -> Option<DeezerResource> {
+    match kind {
+        "track" => Some(DeezerResource::Track(TrackId(id))),
+        "album" => Some(DeezerResource::Album(AlbumId(id))),
+        "artist" => Some(DeezerResource::Artist(ArtistId(id))),
+        "playlist" => Some(DeezerResource::Playlist(PlaylistId(id))),
+        "profile" | "user" => Some(DeezerResource::User(id)),
+        "show" | "podcast" => Some(DeezerResource::Podcast(id)),
+        "episode" => Some(DeezerResource::Episode(id)),
+        "radio" => Some(DeezerResource::Radio(id)),
+        _ => None,
+    }
+}
+
+// Locale prefix of website paths, e.g. "en" or "pt-br"
+fn is_locale(segment: &str) -> bool {
+    let mut parts = segment.split('-');
+    let language = parts.next().unwrap_or_default();
+    language.len() == 2
+        && language.bytes().all(|b| b.is_ascii_alphabetic())
+        && parts.all(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_alphabetic()))
+}
+
+// Path and query of a url on the api host, e.g. "artist/27/top?limit=50" from
+// Artist::tracklist
+pub fn api_url_path(url: &str) -> Result<String, LinkError> {
+    let parsed = Url::parse(url.trim()).map_err(|_| LinkError::InvalidUrl(url.to_string()))?;
+    let api_url = Url::parse(API_URL).map_err(|_| LinkError::InvalidUrl(API_URL.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host() != api_url.host() {
+        let host = parsed.host_str().unwrap_or_default();
+        return Err(LinkError::UnsupportedHost(host.to_string()));
+    }
+    let path = parsed.path().trim_start_matches('/');
+    match parsed.query() {
+        Some(query) => Ok(format!("{path}?{query}")),
+        None => Ok(path.to_string()),
+    }
+}
+
+pub fn is_short_link(url: &str) -> bool {
+    matches!(parse(url), Err(LinkError::ShortLink(_)))
+}
+
+// Parses website links with or without scheme and locale, deezer:// deep links and
+// api urls, short links give LinkError::ShortLink
+pub fn parse(url: &str) -> Result<DeezerResource, LinkError> {
+    let trimmed = url.trim();
+    let lowercase = trimmed.to_ascii_lowercase();
+    let (deep_link, rest) = if let Some(rest) = lowercase.strip_prefix("deezer://") {
+        (true, rest)
+    } else if let Some(rest) = lowercase.strip_prefix("https://") {
+        (false, rest)
+    } else if let Some(rest) = lowercase.strip_prefix("http://") {
+        (false, rest)
+    } else if lowercase.contains("://") {
+        return Err(LinkError::InvalidUrl(url.to_string()));
+    } else {
+        (false, lowercase.as_str())
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    if host.is_empty() {
+        return Err(LinkError::InvalidUrl(url.to_string()));
+    }
+    let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if deep_link && resource(host, 0).is_some() {
+        // deezer://track/3135556
+        segments.insert(0, host);
+    } else {
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        if SHORT_LINK_HOSTS.contains(&host) {
+            return Err(LinkError::ShortLink(trimmed.to_string()));
+        }
+        if host != "deezer.com" && !host.ends_with(".deezer.com") {
+            return Err(LinkError::UnsupportedHost(host.to_string()));
+        }
+    }
+    if segments.first().is_some_and(|s| is_locale(s)) {
+        segments.remove(0);
+    }
+    match segments.as_slice() {
+        [kind, id, ..] => id
+            .parse()
+            .ok()
+            .and_then(|id| resource(kind, id))
+            .ok_or_else(|| LinkError::UnknownResource(url.to_string())),
+        _ => Err(LinkError::UnknownResource(url.to_string())),
+    }
+}
@@ -0,0 +1,368 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use tokio::time::{sleep, Instant};
+
+use crate::models;
+
+// Used as the end of a line when neither deezer nor the next line tell when it stops
+const DEFAULT_LINE_DURATION: u64 = 4000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncedLine {
+    pub start: u64,
+    pub duration: Option<u64>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncedLyrics {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub lines: Vec<SyncedLine>,
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    let (minutes, seconds) = timestamp.trim().split_once(':')?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    let seconds: u64 = seconds.parse().ok()?;
+    // .x is tenths, .xx hundredths and .xxx milliseconds
+    let fraction = match fraction.len() {
+        1 => fraction.parse::<u64>().ok()? * 100,
+        2 => fraction.parse::<u64>().ok()? * 10,
+        3 => fraction.parse::<u64>().ok()?,
+        _ => return None,
+    };
+    Some(minutes * 60_000 + seconds * 1000 + fraction)
+}
+
+fn clock(ms: u64, fraction_separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        fraction_separator,
+        ms % 1000
+    )
+}
+
+impl SyncedLine {
+    pub fn end(&self) -> Option<u64> {
+        self.duration.map(|duration| self.start + duration)
+    }
+}
+
+// Items without timing, such as the blank lines between verses, are skipped
+fn line_from_item(item: &models::gw::LyricsSyncItem) -> Option<SyncedLine> {
+    // an unparsable milliseconds falls back to the lrc timestamp
+    let milliseconds = item
+        .milliseconds
+        .as_ref()
+        .and_then(|ms| ms.trim().parse().ok());
+    let start = match milliseconds {
+        Some(start) => start,
+        None => parse_timestamp(item.lrc_timestamp.as_ref()?.trim_matches(['[', ']']))?,
+    };
+    Some(SyncedLine {
+        start,
+        duration: item.duration.as_ref().and_then(|d| d.trim().parse().ok()),
+        text: item.line.clone(),
+    })
+}
+
+impl From<&models::gw::Lyrics> for SyncedLyrics {
+    fn from(lyrics: &models::gw::Lyrics) -> Self {
+        let mut lines: Vec<SyncedLine> = lyrics
+            .lyrics_sync_json
+            .iter()
+            .flatten()
+            .filter_map(line_from_item)
+            .collect();
+        lines.sort_by_key(|line| line.start);
+        Self {
+            lines,
+            ..Default::default()
+        }
+    }
+}
+
+impl SyncedLyrics {
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn with_track(mut self, track: &models::api::MainTrack) -> Self {
+        self.title = Some(track.title.clone());
+        self.artist = Some(track.artist.name.clone());
+        self.album = Some(track.album.title.clone());
+        self
+    }
+
+    pub fn with_gw_track(mut self, track: &models::gw::Track) -> Self {
+        self.title = Some(track.sng_title.clone());
+        self.artist = Some(track.art_name.clone());
+        self.album = Some(track.alb_title.clone());
+        self
+    }
+
+    // Index of the last line started at position, lines must be sorted by start
+    pub fn line_index_at(&self, position: u64) -> Option<usize> {
+        self.lines
+            .partition_point(|line| line.start <= position)
+            .checked_sub(1)
+    }
+
+    pub fn line_at(&self, position: u64) -> Option<&SyncedLine> {
+        self.lines.get(self.line_index_at(position)?)
+    }
+
+    pub fn cursor(self, position: u64) -> LyricsCursor {
+        LyricsCursor::new(self, position)
+    }
+
+    // End of the line at index, falling back to the start of the next line
+    pub fn line_end(&self, index: usize) -> Option<u64> {
+        let line = self.lines.get(index)?;
+        let next = self.lines.get(index + 1).map(|next| next.start);
+        Some(match (line.end(), next) {
+            (Some(end), Some(next)) => end.min(next),
+            (Some(end), None) => end,
+            (None, Some(next)) => next,
+            (None, None) => line.start + DEFAULT_LINE_DURATION,
+        })
+    }
+
+    fn timed_lines(&self) -> impl Iterator<Item = (u64, u64, &str)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match line.text.trim() {
+                "" => None,
+                text => Some((line.start, self.line_end(index)?, text)),
+            })
+    }
+
+    pub fn to_lrc(&self) -> String {
+        let mut lrc = String::new();
+        let headers = [
+            ("ti", &self.title),
+            ("ar", &self.artist),
+            ("al", &self.album),
+        ];
+        for (tag, value) in headers {
+            if let Some(value) = value {
+                let _ = writeln!(lrc, "[{tag}:{value}]");
+            }
+        }
+        for line in self.lines.iter() {
+            let _ = writeln!(
+                lrc,
+                "[{:02}:{:02}.{:02}]{}",
+                line.start / 60_000,
+                line.start / 1000 % 60,
+                line.start % 1000 / 10,
+                line.text
+            );
+        }
+        lrc
+    }
+
+    // Headers and repeated timestamps are supported, word level (enhanced) timestamps are not
+    pub fn from_lrc(lrc: &str) -> Self {
+        let mut lyrics = SyncedLyrics::default();
+        let mut offset: i64 = 0;
+        for raw_line in lrc.lines() {
+            let mut rest = raw_line.trim();
+            let mut starts = Vec::new();
+            while let Some((tag, remaining)) =
+                rest.strip_prefix('[').and_then(|r| r.split_once(']'))
+            {
+                match parse_timestamp(tag) {
+                    Some(start) => starts.push(start),
+                    None => match tag.split_once(':') {
+                        Some(("ti", value)) => lyrics.title = Some(value.trim().to_string()),
+                        Some(("ar", value)) => lyrics.artist = Some(value.trim().to_string()),
+                        Some(("al", value)) => lyrics.album = Some(value.trim().to_string()),
+                        Some(("offset", value)) => offset = value.trim().parse().unwrap_or(0),
+                        _ => (),
+                    },
+                }
+                rest = remaining;
+            }
+            for start in starts {
+                lyrics.lines.push(SyncedLine {
+                    start,
+                    duration: None,
+                    text: rest.to_string(),
+                });
+            }
+        }
+        // a positive offset shows lines earlier
+        for line in lyrics.lines.iter_mut() {
+            line.start = (line.start as i64 - offset).max(0) as u64;
+        }
+        lyrics.lines.sort_by_key(|line| line.start);
+        lyrics
+    }
+
+    pub fn to_srt(&self) -> String {
+        let mut srt = String::new();
+        for (index, (start, end, text)) in self.timed_lines().enumerate() {
+            let _ = write!(
+                srt,
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                clock(start, ','),
+                clock(end, ','),
+                text
+            );
+        }
+        srt
+    }
+
+    pub fn to_webvtt(&self) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        for (start, end, text) in self.timed_lines() {
+            let _ = write!(
+                vtt,
+                "{} --> {}\n{}\n\n",
+                clock(start, '.'),
+                clock(end, '.'),
+                text
+            );
+        }
+        vtt
+    }
+
+    // Deezer only times whole lines, so the karaoke highlight is spread over the words
+    // of a line proportionally to their length
+    pub fn to_ass(&self) -> String {
+        let ass_clock = |ms: u64| {
+            format!(
+                "{}:{:02}:{:02}.{:02}",
+                ms / 3_600_000,
+                ms / 60_000 % 60,
+                ms / 1000 % 60,
+                ms % 1000 / 10
+            )
+        };
+        let mut ass = String::from("[Script Info]\n");
+        if let Some(title) = &self.title {
+            let _ = writeln!(ass, "Title: {title}");
+        }
+        ass.push_str(concat!(
+            "ScriptType: v4.00+\n",
+            "PlayResX: 1280\n",
+            "PlayResY: 720\n\n",
+            "[V4+ Styles]\n",
+            "Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, ",
+            "BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, ",
+            "BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n",
+            "Style: Default,Arial,48,&H0000FFFF,&H00FFFFFF,&H00000000,&H80000000,",
+            "0,0,0,0,100,100,0,0,1,2,1,2,40,40,40,1\n\n",
+            "[Events]\n",
+            "Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        ));
+        for (start, end, text) in self.timed_lines() {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            let total_chars: u64 = words.iter().map(|w| w.chars().count() as u64).sum();
+            let centiseconds = (end - start) / 10;
+            let mut karaoke = String::new();
+            let mut used = 0;
+            for (index, word) in words.iter().enumerate() {
+                let length = match index + 1 == words.len() {
+                    true => centiseconds - used,
+                    false => centiseconds * word.chars().count() as u64 / total_chars.max(1),
+                };
+                used += length;
+                let separator = if index == 0 { "" } else { " " };
+                let _ = write!(karaoke, "{separator}{{\\k{length}}}{word}");
+            }
+            let _ = writeln!(
+                ass,
+                "Dialogue: 0,{},{},Default,,0,0,0,,{}",
+                ass_clock(start),
+                ass_clock(end),
+                karaoke
+            );
+        }
+        ass
+    }
+}
+
+// Follows playback from a position, the clock starts when the cursor is created or seeked
+#[derive(Debug, Clone)]
+pub struct LyricsCursor {
+    lyrics: SyncedLyrics,
+    // lines are shown offset milliseconds later, negative to show them earlier
+    offset: i64,
+    anchor_position: u64,
+    anchor: Instant,
+    next: usize,
+}
+
+impl LyricsCursor {
+    pub fn new(lyrics: SyncedLyrics, position: u64) -> Self {
+        let mut cursor = Self {
+            lyrics,
+            offset: 0,
+            anchor_position: position,
+            anchor: Instant::now(),
+            next: 0,
+        };
+        cursor.seek(position);
+        cursor
+    }
+
+    pub fn lyrics(&self) -> &SyncedLyrics {
+        &self.lyrics
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    pub fn position(&self) -> u64 {
+        self.anchor_position + self.anchor.elapsed().as_millis() as u64
+    }
+
+    fn lyrics_position(&self, position: u64) -> Option<u64> {
+        u64::try_from(position as i64 - self.offset).ok()
+    }
+
+    pub fn current_index(&self) -> Option<usize> {
+        self.lyrics
+            .line_index_at(self.lyrics_position(self.position())?)
+    }
+
+    pub fn current(&self) -> Option<&SyncedLine> {
+        self.lyrics.lines.get(self.current_index()?)
+    }
+
+    // The line active at the new position is yielded again by next_line
+    pub fn seek(&mut self, position: u64) {
+        self.anchor_position = position;
+        self.anchor = Instant::now();
+        self.next = self.current_index().unwrap_or(0);
+    }
+
+    pub fn set_offset(&mut self, offset: i64) {
+        self.offset = offset;
+        self.seek(self.position());
+    }
+
+    // Waits until the next line is due and returns it with its index, None after the last
+    // line. Dropping the future before it completes does not skip the line.
+    pub async fn next_line(&mut self) -> Option<(usize, SyncedLine)> {
+        let line = self.lyrics.lines.get(self.next)?.clone();
+        let due = line.start as i64 + self.offset - self.position() as i64;
+        if due > 0 {
+            sleep(Duration::from_millis(due as u64)).await;
+        }
+        self.next += 1;
+        Some((self.next - 1, line))
+    }
+}
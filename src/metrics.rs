@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::transport::HttpRequest;
+use crate::API_URL;
+
+// Public api quota: 50 requests per 5 seconds
+pub const QUOTA_LIMIT: usize = 50;
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(5);
+
+// Upper bounds of the latency buckets, in seconds
+pub const LATENCY_BUCKETS: [f64; 9] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+    // count of each bucket of LATENCY_BUCKETS followed by the +Inf one, not cumulative
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum: Duration,
+}
+
+impl Histogram {
+    fn observe(&mut self, latency: Duration) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; LATENCY_BUCKETS.len() + 1];
+        }
+        let seconds = latency.as_secs_f64();
+        let index = LATENCY_BUCKETS.partition_point(|bound| *bound < seconds);
+        self.buckets[index] += 1;
+        self.count += 1;
+        self.sum += latency;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointMetrics {
+    // responses by HTTP status
+    pub statuses: BTreeMap<u16, u64>,
+    // failures by DeezerError::kind()
+    pub errors: BTreeMap<String, u64>,
+    pub latency: Histogram,
+}
+
+impl EndpointMetrics {
+    // Requests which got a response
+    pub fn responses(&self) -> u64 {
+        self.statuses.values().sum()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    // keyed by "api:" + path template such as "api:artist/{id}/top", or "gw:" + method
+    pub endpoints: BTreeMap<String, EndpointMetrics>,
+    // api requests sent within the last QUOTA_WINDOW
+    pub quota_used: usize,
+    pub quota_limit: usize,
+}
+
+impl MetricsSnapshot {
+    pub fn endpoint(&self, endpoint: &str) -> Option<&EndpointMetrics> {
+        self.endpoints.get(endpoint)
+    }
+
+    // Share of the quota used in the current window, from 0 to 1 or more
+    pub fn quota_usage(&self) -> f64 {
+        self.quota_used as f64 / self.quota_limit.max(1) as f64
+    }
+
+    // Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "# HELP deezer_responses_total Responses received, by endpoint and HTTP status.\n",
+        );
+        out.push_str("# TYPE deezer_responses_total counter\n");
+        for (endpoint, metrics) in &self.endpoints {
+            for (status, count) in &metrics.statuses {
+                let endpoint = escape_label(endpoint);
+                let _ = writeln!(
+                    out,
+                    "deezer_responses_total{{endpoint=\"{endpoint}\",status=\"{status}\"}} {count}"
+                );
+            }
+        }
+        out.push_str("# HELP deezer_errors_total Failed requests, by endpoint and error kind.\n");
+        out.push_str("# TYPE deezer_errors_total counter\n");
+        for (endpoint, metrics) in &self.endpoints {
+            for (kind, count) in &metrics.errors {
+                let endpoint = escape_label(endpoint);
+                let _ = writeln!(
+                    out,
+                    "deezer_errors_total{{endpoint=\"{endpoint}\",kind=\"{kind}\"}} {count}"
+                );
+            }
+        }
+        out.push_str(
+            "# HELP deezer_request_duration_seconds Latency of the requests sent, by endpoint.\n",
+        );
+        out.push_str("# TYPE deezer_request_duration_seconds histogram\n");
+        for (endpoint, metrics) in &self.endpoints {
+            let latency = &metrics.latency;
+            if latency.count == 0 {
+                continue;
+            }
+            let endpoint = escape_label(endpoint);
+            let mut cumulative = 0;
+            for (index, count) in latency.buckets.iter().enumerate() {
+                cumulative += count;
+                let le = match LATENCY_BUCKETS.get(index) {
+                    Some(bound) => bound.to_string(),
+                    None => "+Inf".to_string(),
+                };
+                let _ = writeln!(
+                    out,
+                    "deezer_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",le=\"{le}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "deezer_request_duration_seconds_sum{{endpoint=\"{endpoint}\"}} {}",
+                latency.sum.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "deezer_request_duration_seconds_count{{endpoint=\"{endpoint}\"}} {}",
+                latency.count
+            );
+        }
+        out.push_str("# HELP deezer_quota_used Api requests sent in the current quota window.\n");
+        out.push_str("# TYPE deezer_quota_used gauge\n");
+        let _ = writeln!(out, "deezer_quota_used {}", self.quota_used);
+        out.push_str("# HELP deezer_quota_limit Api requests allowed per quota window.\n");
+        out.push_str("# TYPE deezer_quota_limit gauge\n");
+        let _ = writeln!(out, "deezer_quota_limit {}", self.quota_limit);
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Ids in the path are replaced so that every track shares "api:track/{id}"
+pub fn api_path_template(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or(path);
+    let segments: Vec<&str> = path
+        .split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                "{id}"
+            } else if segment.starts_with("isrc:") {
+                "isrc:{isrc}"
+            } else if segment.starts_with("upc:") {
+                "upc:{upc}"
+            } else {
+                segment
+            }
+        })
+        .collect();
+    segments.join("/")
+}
+
+// Metrics label of an api path or url, e.g. "api:track/{id}"
+pub(crate) fn api_endpoint_label(path: &str) -> String {
+    let path = path.strip_prefix(API_URL).unwrap_or(path);
+    format!("api:{}", api_path_template(path))
+}
+
+// Metrics label of a request, None for urls outside the apis such as cover art
+pub(crate) fn request_endpoint(request: &HttpRequest) -> Option<String> {
+    if let Some(method) = request.gw_method() {
+        return Some(format!("gw:{method}"));
+    }
+    request.api_path().map(api_endpoint_label)
+}
+
+#[derive(Debug, Default)]
+struct State {
+    endpoints: BTreeMap<String, EndpointMetrics>,
+    quota: VecDeque<Instant>,
+}
+
+impl State {
+    fn prune_quota(&mut self, now: Instant) {
+        while let Some(sent) = self.quota.front() {
+            if now.duration_since(*sent) < QUOTA_WINDOW {
+                break;
+            }
+            self.quota.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    state: Mutex<State>,
+}
+
+impl Metrics {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Called before sending, api requests count against the quota
+    pub fn record_sent(&self, endpoint: &str) {
+        if endpoint.starts_with("api:") {
+            let now = Instant::now();
+            let mut state = self.state();
+            state.prune_quota(now);
+            state.quota.push_back(now);
+        }
+    }
+
+    pub fn record_response(&self, endpoint: &str, status: u16, latency: Duration) {
+        let mut state = self.state();
+        let metrics = state.endpoints.entry(endpoint.to_string()).or_default();
+        *metrics.statuses.entry(status).or_default() += 1;
+        metrics.latency.observe(latency);
+    }
+
+    // latency is None for errors found after the response, such as an api error body
+    pub fn record_error(&self, endpoint: &str, kind: &str, latency: Option<Duration>) {
+        let mut state = self.state();
+        let metrics = state.endpoints.entry(endpoint.to_string()).or_default();
+        *metrics.errors.entry(kind.to_string()).or_default() += 1;
+        if let Some(latency) = latency {
+            metrics.latency.observe(latency);
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut state = self.state();
+        state.prune_quota(Instant::now());
+        MetricsSnapshot {
+            endpoints: state.endpoints.clone(),
+            quota_used: state.quota.len(),
+            quota_limit: QUOTA_LIMIT,
+        }
+    }
+
+    pub fn reset(&self) {
+        let mut state = self.state();
+        state.endpoints.clear();
+    }
+}
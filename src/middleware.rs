@@ -0,0 +1,34 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::transport::{HttpRequest, HttpResponse};
+use crate::DeezerError;
+
+// Hooks run around every request sent through the transport, responses served
+// from the cache never reach them. All methods default to doing nothing.
+pub trait Middleware: Debug + Send + Sync {
+    // May change the request, e.g. to add a signature parameter
+    fn before_request(&self, _request: &mut HttpRequest) {}
+
+    // Called for every response, api errors included since they come back as
+    // a body with an "error" object
+    fn after_response(&self, _request: &HttpRequest, _response: &HttpResponse, _latency: Duration) {
+    }
+
+    // Called when the transport fails to return a response
+    fn on_error(&self, _request: &HttpRequest, _error: &DeezerError, _latency: Duration) {}
+}
+
+impl<T: Middleware + ?Sized> Middleware for std::sync::Arc<T> {
+    fn before_request(&self, request: &mut HttpRequest) {
+        (**self).before_request(request)
+    }
+
+    fn after_response(&self, request: &HttpRequest, response: &HttpResponse, latency: Duration) {
+        (**self).after_response(request, response, latency)
+    }
+
+    fn on_error(&self, request: &HttpRequest, error: &DeezerError, latency: Duration) {
+        (**self).on_error(request, error, latency)
+    }
+}
@@ -1,16 +1,19 @@
 use serde_json::Value;
 use std::collections::HashMap;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+use super::fields;
+use super::ids::{AlbumId, ArtistId, GenreId, Isrc, PlaylistId, TrackId, Upc};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MainTrack {
-    pub id: u64,
+    pub id: TrackId,
     pub readable: bool,
     pub title: String,
     pub title_short: String,
     pub title_version: Option<String>,
-    pub isrc: String,
+    pub isrc: Isrc,
     pub link: String,
     pub share: String,
     pub duration: u64,
@@ -22,7 +25,9 @@ pub struct MainTrack {
     pub explicit_content_lyrics: u8,
     pub explicit_content_cover: u8,
     pub preview: String,
+    #[serde(with = "fields::float")]
     pub bpm: f32,
+    #[serde(with = "fields::float")]
     pub gain: f32,
     pub available_countries: Vec<String>,
     pub contributors: Vec<Contributor>,
@@ -33,11 +38,11 @@ pub struct MainTrack {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MainAlbum {
-    pub id: u64,
+    pub id: AlbumId,
     pub title: String,
-    pub upc: String,
+    pub upc: Upc,
     pub link: String,
     pub share: String,
     pub cover: String,
@@ -46,7 +51,7 @@ pub struct MainAlbum {
     pub cover_big: String,
     pub cover_xl: String,
     pub md5_image: String,
-    pub genre_id: i64,
+    pub genre_id: GenreId,
     pub genres: GenresData,
     pub label: String,
     pub nb_tracks: u64,
@@ -66,33 +71,33 @@ pub struct MainAlbum {
     pub tracks: TracksData,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GenresData {
     pub data: Vec<Genre>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Genre {
-    pub id: u64,
+    pub id: GenreId,
     pub name: String,
     pub picture: String,
     #[serde(rename = "type")]
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TracksData {
     pub data: Vec<Track>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Track {
-    pub id: u64,
+    pub id: TrackId,
     pub readable: bool,
     pub title: String,
     pub title_short: String,
     pub title_version: Option<String>,
-    pub isrc: Option<String>,
+    pub isrc: Option<Isrc>,
     pub link: Option<String>,
     pub duration: u64,
     pub track_position: Option<u64>,
@@ -113,14 +118,14 @@ pub struct Track {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct AlternativeTrack {
-    pub id: u64,
+    pub id: TrackId,
     pub readable: bool,
     pub title: String,
     pub title_short: String,
     pub title_version: Option<String>,
-    pub isrc: Option<String>,
+    pub isrc: Option<Isrc>,
     pub link: Option<String>,
     pub duration: u64,
     pub track_position: Option<u64>,
@@ -140,9 +145,9 @@ pub struct AlternativeTrack {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Album {
-    pub id: u64,
+    pub id: AlbumId,
     pub title: String,
     pub link: Option<String>,
     pub cover: String,
@@ -151,7 +156,7 @@ pub struct Album {
     pub cover_big: Option<String>,
     pub cover_xl: Option<String>,
     pub md5_image: String,
-    pub genre_id: Option<i64>,
+    pub genre_id: Option<GenreId>,
     pub nb_tracks: Option<u64>,
     pub fans: Option<i64>,
     pub release_date: Option<String>,
@@ -164,9 +169,9 @@ pub struct Album {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Contributor {
-    pub id: u64,
+    pub id: ArtistId,
     pub name: String,
     pub link: String,
     pub share: String,
@@ -182,9 +187,9 @@ pub struct Contributor {
     pub role: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Artist {
-    pub id: u64,
+    pub id: ArtistId,
     pub name: String,
     pub link: Option<String>,
     pub share: Option<String>,
@@ -201,9 +206,9 @@ pub struct Artist {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Playlist {
-    pub id: u64,
+    pub id: PlaylistId,
     pub title: String,
     pub public: bool,
     pub nb_tracks: Option<u64>,
@@ -223,7 +228,7 @@ pub struct Playlist {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct User {
     pub id: u64,
     pub name: String,
@@ -242,9 +247,76 @@ pub struct User {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct RelatedArtist {
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MainPlaylist {
+    pub id: PlaylistId,
+    pub title: String,
+    pub description: Option<String>,
+    pub duration: u64,
+    pub public: bool,
+    pub is_loved_track: bool,
+    pub collaborative: bool,
+    pub nb_tracks: u64,
+    pub fans: u64,
+    pub link: String,
+    pub share: String,
+    pub picture: String,
+    pub picture_small: String,
+    pub picture_medium: String,
+    pub picture_big: String,
+    pub picture_xl: String,
+    pub checksum: String,
+    pub tracklist: String,
+    pub creation_date: String,
+    pub md5_image: String,
+    pub picture_type: String,
+    pub creator: User,
+    #[serde(rename = "type")]
+    pub type_field: String,
+    pub tracks: TracksData,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Podcast {
+    pub id: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub available: Option<bool>,
+    pub fans: Option<u64>,
+    pub link: String,
+    pub share: Option<String>,
+    pub picture: String,
+    pub picture_small: String,
+    pub picture_medium: String,
+    pub picture_big: String,
+    pub picture_xl: String,
+    #[serde(rename = "type")]
+    pub type_field: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Episode {
     pub id: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub available: bool,
+    pub release_date: String,
+    pub duration: u64,
+    pub link: String,
+    pub share: Option<String>,
+    pub picture: String,
+    pub picture_small: String,
+    pub picture_medium: String,
+    pub picture_big: String,
+    pub picture_xl: String,
+    pub podcast: Podcast,
+    #[serde(rename = "type")]
+    pub type_field: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RelatedArtist {
+    pub id: ArtistId,
     pub name: String,
     pub link: String,
     pub picture: String,
@@ -260,9 +332,9 @@ pub struct RelatedArtist {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Editorial {
-    pub id: u64,
+    pub id: GenreId,
     pub name: String,
     pub picture: String,
     pub picture_small: String,
@@ -273,14 +345,14 @@ pub struct Editorial {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GenreRadios {
-    pub id: u64,
+    pub id: GenreId,
     pub title: String,
     pub radios: Vec<Radio>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Radio {
     pub id: u64,
     pub title: String,
@@ -295,7 +367,7 @@ pub struct Radio {
     pub type_field: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Info {
     pub country_iso: String,
     pub country: String,
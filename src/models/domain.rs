@@ -0,0 +1,530 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::fields::Date;
+use super::ids::{AlbumId, ArtistId, GenreId, Isrc, TrackId, Upc};
+use super::{api, gw};
+use crate::DeezerError;
+
+// Source independent models, built from api and gw models. Fields a source
+// does not provide are None, merge fills them from another source
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Artist {
+    pub id: ArtistId,
+    pub name: String,
+    pub picture: Option<String>,
+    pub md5_image: Option<String>,
+    pub nb_album: Option<u64>,
+    pub nb_fan: Option<u64>,
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Album {
+    pub id: AlbumId,
+    pub title: String,
+    pub upc: Option<Upc>,
+    pub label: Option<String>,
+    pub genre_id: Option<GenreId>,
+    pub release_date: Option<String>,
+    pub record_type: Option<String>,
+    pub nb_tracks: Option<u64>,
+    pub fans: Option<u64>,
+    pub explicit: Option<bool>,
+    pub md5_image: Option<String>,
+    pub artist: Option<Artist>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Track {
+    pub id: TrackId,
+    pub title: String,
+    pub version: Option<String>,
+    pub isrc: Option<Isrc>,
+    // seconds
+    pub duration: Option<u64>,
+    pub track_number: Option<u64>,
+    pub disk_number: Option<u64>,
+    pub rank: Option<u64>,
+    pub explicit: Option<bool>,
+    pub release_date: Option<String>,
+    pub bpm: Option<f32>,
+    pub gain: Option<f32>,
+    pub md5_image: Option<String>,
+    // main artist first
+    pub artists: Vec<Artist>,
+    pub album: Option<Album>,
+}
+
+// gw gains are strings, empty when unknown
+fn number<T: FromStr>(value: &str) -> Option<T> {
+    value.trim().parse().ok()
+}
+
+fn text(value: &str) -> Option<String> {
+    match value.trim() {
+        "" | "0000-00-00" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+// Tracks without isrc and albums without upc have an empty code
+fn code<T: ToString>(code: T) -> Option<T> {
+    Some(code).filter(|code| !code.to_string().is_empty())
+}
+
+fn date(date: Option<Date>) -> Option<String> {
+    date.map(|date| date.to_string())
+}
+
+fn fill<T>(field: &mut Option<T>, other: Option<T>) {
+    if field.is_none() {
+        *field = other;
+    }
+}
+
+fn fill_text(field: &mut String, other: String) {
+    if field.is_empty() {
+        *field = other;
+    }
+}
+
+impl Artist {
+    // Keeps the fields of self and takes the missing ones from other
+    pub fn merge(mut self, other: Artist) -> Artist {
+        fill_text(&mut self.name, other.name);
+        fill(&mut self.picture, other.picture);
+        fill(&mut self.md5_image, other.md5_image);
+        fill(&mut self.nb_album, other.nb_album);
+        fill(&mut self.nb_fan, other.nb_fan);
+        fill(&mut self.role, other.role);
+        self
+    }
+}
+
+impl Album {
+    // Keeps the fields of self and takes the missing ones from other
+    pub fn merge(mut self, other: Album) -> Album {
+        fill_text(&mut self.title, other.title);
+        fill(&mut self.upc, other.upc);
+        fill(&mut self.label, other.label);
+        fill(&mut self.genre_id, other.genre_id);
+        fill(&mut self.release_date, other.release_date);
+        fill(&mut self.record_type, other.record_type);
+        fill(&mut self.nb_tracks, other.nb_tracks);
+        fill(&mut self.fans, other.fans);
+        fill(&mut self.explicit, other.explicit);
+        fill(&mut self.md5_image, other.md5_image);
+        self.artist = match (self.artist, other.artist) {
+            (Some(artist), Some(other)) => Some(artist.merge(other)),
+            (artist, other) => artist.or(other),
+        };
+        self
+    }
+}
+
+impl Track {
+    // Keeps the fields of self and takes the missing ones from other, artists
+    // are merged by id
+    pub fn merge(mut self, other: Track) -> Track {
+        fill_text(&mut self.title, other.title);
+        fill(&mut self.version, other.version);
+        fill(&mut self.isrc, other.isrc);
+        fill(&mut self.duration, other.duration);
+        fill(&mut self.track_number, other.track_number);
+        fill(&mut self.disk_number, other.disk_number);
+        fill(&mut self.rank, other.rank);
+        fill(&mut self.explicit, other.explicit);
+        fill(&mut self.release_date, other.release_date);
+        fill(&mut self.bpm, other.bpm);
+        fill(&mut self.gain, other.gain);
+        fill(&mut self.md5_image, other.md5_image);
+        for artist in other.artists {
+            match self.artists.iter_mut().find(|a| a.id == artist.id) {
+                Some(known) => *known = std::mem::take(known).merge(artist),
+                None => self.artists.push(artist),
+            }
+        }
+        self.album = match (self.album, other.album) {
+            (Some(album), Some(other)) => Some(album.merge(other)),
+            (album, other) => album.or(other),
+        };
+        self
+    }
+}
+
+impl From<api::Artist> for Artist {
+    fn from(artist: api::Artist) -> Self {
+        Artist {
+            id: artist.id,
+            name: artist.name,
+            picture: artist.picture_xl.or(artist.picture),
+            nb_album: artist.nb_album,
+            nb_fan: artist.nb_fan,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<api::Contributor> for Artist {
+    fn from(artist: api::Contributor) -> Self {
+        Artist {
+            id: artist.id,
+            name: artist.name,
+            picture: text(&artist.picture_xl),
+            role: text(&artist.role),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<api::RelatedArtist> for Artist {
+    fn from(artist: api::RelatedArtist) -> Self {
+        Artist {
+            id: artist.id,
+            name: artist.name,
+            picture: text(&artist.picture_xl),
+            nb_album: Some(artist.nb_album),
+            nb_fan: Some(artist.nb_fan),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<gw::Artist> for Artist {
+    fn from(artist: gw::Artist) -> Self {
+        Artist {
+            id: artist.art_id,
+            name: artist.art_name,
+            md5_image: text(&artist.art_picture),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<api::MainAlbum> for Album {
+    fn from(album: api::MainAlbum) -> Self {
+        Album {
+            id: album.id,
+            title: album.title,
+            upc: code(album.upc),
+            label: text(&album.label),
+            genre_id: Some(album.genre_id),
+            release_date: text(&album.release_date),
+            record_type: text(&album.record_type),
+            nb_tracks: Some(album.nb_tracks),
+            fans: Some(album.fans),
+            explicit: Some(album.explicit_lyrics),
+            md5_image: text(&album.md5_image),
+            artist: Some(album.artist.into()),
+        }
+    }
+}
+
+impl From<api::Album> for Album {
+    fn from(album: api::Album) -> Self {
+        // albums nested in tracks come without artist
+        let artist = match album.artist.id {
+            ArtistId(0) => None,
+            _ => Some(album.artist.into()),
+        };
+        Album {
+            id: album.id,
+            title: album.title,
+            genre_id: album.genre_id,
+            release_date: album.release_date.as_deref().and_then(text),
+            record_type: album.record_type.as_deref().and_then(text),
+            nb_tracks: album.nb_tracks,
+            fans: album.fans.and_then(|fans| fans.try_into().ok()),
+            explicit: album.explicit_lyrics,
+            md5_image: text(&album.md5_image),
+            artist,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<gw::Album> for Album {
+    fn from(album: gw::Album) -> Self {
+        let artist = Artist {
+            id: album.art_id,
+            name: album.art_name,
+            ..Default::default()
+        };
+        Album {
+            id: album.alb_id,
+            title: album.alb_title,
+            label: text(&album.label_name),
+            genre_id: Some(album.genre_id),
+            release_date: date(album.physical_release_date.or(album.digital_release_date)),
+            nb_tracks: album.number_track,
+            fans: album.nb_fan.try_into().ok(),
+            md5_image: text(&album.alb_picture),
+            artist: Some(artist),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<api::MainTrack> for Track {
+    fn from(track: api::MainTrack) -> Self {
+        let mut artists = vec![Artist::from(track.artist)];
+        for contributor in track.contributors {
+            if contributor.id != artists[0].id {
+                artists.push(contributor.into());
+            }
+        }
+        Track {
+            id: track.id,
+            title: track.title,
+            version: track.title_version.as_deref().and_then(text),
+            isrc: code(track.isrc),
+            duration: Some(track.duration),
+            track_number: Some(track.track_position),
+            disk_number: Some(track.disk_number),
+            rank: Some(track.rank),
+            explicit: Some(track.explicit_lyrics),
+            release_date: text(&track.release_date),
+            bpm: Some(track.bpm).filter(|bpm| *bpm > 0.0),
+            gain: Some(track.gain),
+            md5_image: text(&track.md5_image),
+            artists,
+            album: Some(track.album.into()),
+        }
+    }
+}
+
+impl From<api::Track> for Track {
+    fn from(track: api::Track) -> Self {
+        let mut artists = vec![Artist::from(track.artist)];
+        for contributor in track.contributors {
+            if contributor.id != artists[0].id {
+                artists.push(contributor.into());
+            }
+        }
+        // album lists leave the album out of their tracks
+        let album = match track.album.id {
+            AlbumId(0) => None,
+            _ => Some(track.album.into()),
+        };
+        Track {
+            id: track.id,
+            title: track.title,
+            version: track.title_version.as_deref().and_then(text),
+            isrc: track.isrc.and_then(code),
+            duration: Some(track.duration),
+            track_number: track.track_position,
+            disk_number: track.disk_number,
+            rank: Some(track.rank),
+            explicit: Some(track.explicit_lyrics),
+            md5_image: text(&track.md5_image),
+            artists,
+            album,
+            ..Default::default()
+        }
+    }
+}
+
+// Fields shared by gw tracks and songs
+struct GwTrack {
+    sng_id: TrackId,
+    sng_title: String,
+    version: Option<String>,
+    isrc: Isrc,
+    duration: Option<Duration>,
+    track_number: Option<u64>,
+    disk_number: Option<u64>,
+    rank: Option<u64>,
+    explicit_lyrics: Option<bool>,
+    release_date: Option<Date>,
+    gain: Option<String>,
+    artists: Vec<gw::Artist>,
+    art_id: ArtistId,
+    art_name: String,
+    alb_id: AlbumId,
+    alb_title: String,
+    alb_picture: String,
+}
+
+impl From<GwTrack> for Track {
+    fn from(track: GwTrack) -> Self {
+        let mut artists: Vec<Artist> = track.artists.into_iter().map(Artist::from).collect();
+        if !artists.iter().any(|a| a.id == track.art_id) {
+            let main = Artist {
+                id: track.art_id,
+                name: track.art_name,
+                ..Default::default()
+            };
+            artists.insert(0, main);
+        }
+        let album = Album {
+            id: track.alb_id,
+            title: track.alb_title,
+            md5_image: text(&track.alb_picture),
+            ..Default::default()
+        };
+        Track {
+            id: track.sng_id,
+            title: track.sng_title,
+            version: track.version.as_deref().and_then(text),
+            isrc: code(track.isrc),
+            duration: track.duration.map(|duration| duration.as_secs()),
+            track_number: track.track_number,
+            disk_number: track.disk_number,
+            rank: track.rank,
+            explicit: track.explicit_lyrics,
+            release_date: date(track.release_date),
+            gain: track.gain.as_deref().and_then(number),
+            md5_image: text(&track.alb_picture),
+            artists,
+            album: Some(album),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<gw::Track> for Track {
+    fn from(track: gw::Track) -> Self {
+        Track::from(GwTrack {
+            sng_id: track.sng_id,
+            sng_title: track.sng_title,
+            version: track.version,
+            isrc: track.isrc,
+            duration: track.duration,
+            track_number: track.track_number,
+            disk_number: track.disk_number,
+            rank: track.rank_sng,
+            explicit_lyrics: track.explicit_lyrics,
+            release_date: track.physical_release_date,
+            gain: track.gain,
+            artists: track.artists,
+            art_id: track.art_id,
+            art_name: track.art_name,
+            alb_id: track.alb_id,
+            alb_title: track.alb_title,
+            alb_picture: track.alb_picture,
+        })
+    }
+}
+
+impl From<gw::Song> for Track {
+    fn from(song: gw::Song) -> Self {
+        Track::from(GwTrack {
+            sng_id: song.sng_id,
+            sng_title: song.sng_title,
+            version: song.version,
+            isrc: song.isrc,
+            duration: song.duration,
+            track_number: song.track_number,
+            disk_number: song.disk_number,
+            rank: song.rank,
+            explicit_lyrics: song.explicit_lyrics,
+            release_date: song.physical_release_date,
+            gain: song.gain,
+            artists: song.artists,
+            art_id: song.art_id,
+            art_name: song.art_name,
+            alb_id: song.alb_id,
+            alb_title: song.alb_title,
+            alb_picture: song.alb_picture,
+        })
+    }
+}
+
+// Bytes per format, None when the format is not available
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FileSizes {
+    pub aac_64: Option<u64>,
+    pub mp3_64: Option<u64>,
+    pub mp3_128: Option<u64>,
+    pub mp3_256: Option<u64>,
+    pub mp3_320: Option<u64>,
+    pub mp4_ra1: Option<u64>,
+    pub mp4_ra2: Option<u64>,
+    pub mp4_ra3: Option<u64>,
+    pub mhm1_ra1: Option<u64>,
+    pub mhm1_ra2: Option<u64>,
+    pub mhm1_ra3: Option<u64>,
+    pub flac: Option<u64>,
+}
+
+impl From<&gw::Track> for FileSizes {
+    fn from(track: &gw::Track) -> Self {
+        let size = |value: Option<u64>| value.filter(|size| *size > 0);
+        FileSizes {
+            aac_64: size(track.filesize_aac_64),
+            mp3_64: size(track.filesize_mp3_64),
+            mp3_128: size(track.filesize_mp3_128),
+            mp3_256: size(track.filesize_mp3_256),
+            mp3_320: size(track.filesize_mp3_320),
+            mp4_ra1: size(track.filesize_mp4_ra1),
+            mp4_ra2: size(track.filesize_mp4_ra2),
+            mp4_ra3: size(track.filesize_mp4_ra3),
+            mhm1_ra1: size(track.filesize_mhm1_ra1),
+            mhm1_ra2: size(track.filesize_mhm1_ra2),
+            mhm1_ra3: size(track.filesize_mhm1_ra3),
+            flac: size(track.filesize_flac),
+        }
+    }
+}
+
+// Built by Deezer::track_full, errors lists the sources which failed
+#[derive(Debug)]
+pub struct FullTrack {
+    // api fields first, bpm and gain come from the api
+    pub track: Track,
+    pub file_sizes: FileSizes,
+    // names by role, e.g. "composer"
+    pub contributors: BTreeMap<String, Vec<String>>,
+    pub lyrics: Option<gw::Lyrics>,
+    pub errors: Vec<DeezerError>,
+}
+
+impl FullTrack {
+    // Fails only when neither the api nor gw returned the track
+    pub(crate) fn from_parts(
+        api: Result<api::MainTrack, DeezerError>,
+        gw: Result<gw::TrackData, DeezerError>,
+        lyrics: Result<gw::Lyrics, DeezerError>,
+    ) -> Result<FullTrack, DeezerError> {
+        let mut errors = Vec::new();
+        let api = api.map_err(|err| errors.push(err)).ok();
+        let gw = gw.map_err(|err| errors.push(err)).ok();
+        let lyrics = match lyrics {
+            Ok(lyrics) => Some(lyrics),
+            Err(err) => {
+                errors.push(err);
+                gw.as_ref().and_then(|data| data.lyrics.clone())
+            }
+        };
+        let (file_sizes, contributors) = match &gw {
+            Some(data) => (
+                FileSizes::from(&data.track),
+                match &data.track.sng_contributors {
+                    Some(gw::SngContributors::SngContributors(roles)) => {
+                        roles.clone().into_iter().collect()
+                    }
+                    _ => BTreeMap::new(),
+                },
+            ),
+            None => Default::default(),
+        };
+        let gw = gw.map(|data| Track::from(data.track));
+        let track = match (api, gw) {
+            (Some(api), Some(gw)) => Track::from(api).merge(gw),
+            (Some(api), None) => Track::from(api),
+            (None, Some(gw)) => gw,
+            (None, None) => return Err(errors.remove(0)),
+        };
+        Ok(FullTrack {
+            track,
+            file_sizes,
+            contributors,
+            lyrics,
+            errors,
+        })
+    }
+}
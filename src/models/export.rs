@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Field names of exported models. Keys are sorted either way, so the same
+// model always gives the same json
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    // as deezer sends them, e.g. SNG_ID for gw and md5_image for the api,
+    // the json can be deserialized back into the model. Values keep deezer's
+    // encoding, except typed gw fields which are written the way gw usually
+    // sends them, e.g. "320" for a duration sent as 320
+    #[default]
+    Deezer,
+    // snake_case for both sources, e.g. sng_id, and type for __TYPE__. Keys
+    // which would collide, such as TYPE and __TYPE__ of gw tracks, are only
+    // lowercased
+    Normalized,
+}
+
+pub fn to_value<T: Serialize>(model: &T, format: ExportFormat) -> serde_json::Result<Value> {
+    let value = serde_json::to_value(model)?;
+    Ok(match format {
+        ExportFormat::Deezer => value,
+        ExportFormat::Normalized => normalize(value),
+    })
+}
+
+pub fn to_json<T: Serialize>(model: &T, format: ExportFormat) -> serde_json::Result<String> {
+    serde_json::to_string(&to_value(model, format)?)
+}
+
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut names: HashMap<String, usize> = HashMap::new();
+            for key in map.keys() {
+                *names.entry(snake_case(key)).or_default() += 1;
+            }
+            let map: Map<String, Value> = map
+                .into_iter()
+                .map(|(key, value)| {
+                    let name = snake_case(&key);
+                    let name = match names[&name] {
+                        1 => name,
+                        _ => key.to_lowercase(),
+                    };
+                    (name, normalize(value))
+                })
+                .collect();
+            Value::Object(map)
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize).collect()),
+        value => value,
+    }
+}
+
+// SNG_ID -> sng_id, __TYPE__ -> type, trackToken -> track_token
+fn snake_case(key: &str) -> String {
+    let mut snake = String::with_capacity(key.len() + 2);
+    let mut previous: Option<char> = None;
+    for c in key.trim_matches('_').chars() {
+        if c.is_ascii_uppercase()
+            && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+        previous = Some(c);
+    }
+    snake
+}
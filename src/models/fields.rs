@@ -0,0 +1,263 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateError(pub String);
+
+impl fmt::Display for DateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid date {:?}", self.0)
+    }
+}
+
+// Calendar date as gw sends it, e.g. 2001-03-07
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl FromStr for Date {
+    type Err = DateError;
+
+    fn from_str(date: &str) -> Result<Self, Self::Err> {
+        let invalid = || DateError(date.to_string());
+        let mut parts = date.trim().splitn(3, '-');
+        let mut part = |len: usize| {
+            parts
+                .next()
+                .filter(|part| part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|part| part.parse::<u16>().ok())
+                .ok_or_else(invalid)
+        };
+        let (year, month, day) = (part(4)?, part(2)?, part(2)?);
+        if year == 0 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(invalid());
+        }
+        Ok(Date {
+            year,
+            month: month as u8,
+            day: day as u8,
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+// gw sends numbers either as numbers or as strings, and an empty string or null
+// when there is no value, the value is returned as text
+struct LenientVisitor;
+
+impl<'de> Visitor<'de> for LenientVisitor {
+    type Value = Option<String>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, a boolean or a string")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(Some(u8::from(value).to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        let value = value.trim();
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+fn lenient<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    deserializer.deserialize_any(LenientVisitor)
+}
+
+// Fields below are used with #[serde(default, with = "...")], a missing field is None.
+// They are serialized back as gw sends them, as strings and empty when None
+fn serialize_text<S, T>(value: Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: fmt::Display,
+{
+    match value {
+        Some(value) => serializer.collect_str(&value),
+        None => serializer.serialize_str(""),
+    }
+}
+
+pub(crate) mod number {
+    use super::*;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr,
+    {
+        match lenient(deserializer)? {
+            Some(text) => match text.parse() {
+                Ok(number) => Ok(Some(number)),
+                Err(_) => Err(de::Error::custom(format!("invalid number {text:?}"))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: fmt::Display,
+    {
+        serialize_text(value.as_ref(), serializer)
+    }
+}
+
+// "0" and "1", or false and true
+pub(crate) mod flag {
+    use super::*;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<bool>, D::Error> {
+        match lenient(deserializer)?.as_deref() {
+            Some("1") | Some("true") => Ok(Some(true)),
+            Some("0") | Some("false") => Ok(Some(false)),
+            Some(text) => Err(de::Error::custom(format!("invalid flag {text:?}"))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<bool>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_text(value.map(u8::from), serializer)
+    }
+}
+
+// Whole seconds
+pub(crate) mod seconds {
+    use super::*;
+    use std::time::Duration;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(number::deserialize(deserializer)?.map(Duration::from_secs))
+    }
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_text(value.map(|duration| duration.as_secs()), serializer)
+    }
+}
+
+// Written with the shortest decimals giving back the f32, 122.7 rather than
+// 122.69999694824219
+pub(crate) mod float {
+    use super::*;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        f32::deserialize(deserializer)
+    }
+
+    pub fn serialize<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = value.to_string().parse().unwrap_or(f64::from(*value));
+        serializer.serialize_f64(value)
+    }
+}
+
+// Ids, gw sends "" when there is none which gives the default id 0. The default
+// id is serialized back as ""
+pub(crate) mod id {
+    use super::*;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr + Default,
+        T::Err: fmt::Display,
+    {
+        match lenient(deserializer)? {
+            Some(text) => text.parse().map_err(de::Error::custom),
+            None => Ok(T::default()),
+        }
+    }
+
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: fmt::Display + Default + PartialEq,
+    {
+        serialize_text(Some(value).filter(|id| **id != T::default()), serializer)
+    }
+}
+
+// gw uses 0000-00-00 for unknown dates, which is also how None is written back
+pub(crate) mod date {
+    use super::*;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Date>, D::Error> {
+        match lenient(deserializer)? {
+            Some(text) if text == "0000-00-00" => Ok(None),
+            Some(text) => text.parse().map(Some).map_err(de::Error::custom),
+            None => Ok(None),
+        }
+    }
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Date>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(date) => serializer.collect_str(date),
+            None => serializer.serialize_str("0000-00-00"),
+        }
+    }
+}
@@ -1,8 +1,12 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Deserialize)]
+use super::fields::{self, Date};
+use super::ids::{self, AlbumId, ArtistId, GenreId, TrackId};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct TrackData {
     #[serde(rename = "DATA")]
@@ -12,71 +16,83 @@ pub struct TrackData {
     pub related_albums: RelatedAlbums,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Track {
-    pub sng_id: String,
+    #[serde(with = "fields::id")]
+    pub sng_id: TrackId,
     pub product_track_id: Option<String>,
     pub upload_id: Option<i64>,
     pub sng_title: String,
-    pub art_id: String,
+    #[serde(with = "fields::id")]
+    pub art_id: ArtistId,
     pub provider_id: String,
     pub art_name: String,
-    pub digital_release_date: Option<String>,
+    #[serde(default, with = "fields::date")]
+    pub digital_release_date: Option<Date>,
     pub artist_is_dummy: Option<bool>,
     pub artists: Vec<Artist>,
-    pub alb_id: String,
+    #[serde(with = "fields::id")]
+    pub alb_id: AlbumId,
     pub alb_title: String,
     #[serde(rename = "TYPE")]
     pub type_field: i64,
     pub video: Option<bool>,
-    pub duration: String,
+    #[serde(default, with = "fields::seconds")]
+    pub duration: Option<Duration>,
     pub alb_picture: String,
     pub art_picture: Option<String>,
-    pub rank_sng: String,
-    #[serde(default)]
-    pub filesize_aac_64: String,
-    #[serde(default)]
-    pub filesize_mp3_64: String,
-    #[serde(default)]
-    pub filesize_mp3_128: String,
-    #[serde(default)]
-    pub filesize_mp3_256: String,
-    #[serde(default)]
-    pub filesize_mp3_320: String,
-    #[serde(default)]
-    pub filesize_mp4_ra1: String,
-    #[serde(default)]
-    pub filesize_mp4_ra2: String,
-    #[serde(default)]
-    pub filesize_mp4_ra3: String,
-    #[serde(default)]
-    pub filesize_mhm1_ra1: String,
-    #[serde(default)]
-    pub filesize_mhm1_ra2: String,
-    #[serde(default)]
-    pub filesize_mhm1_ra3: String,
-    #[serde(default)]
-    pub filesize_flac: String,
-    pub filesize: String,
+    #[serde(default, with = "fields::number")]
+    pub rank_sng: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_aac_64: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_64: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_128: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_256: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_320: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp4_ra1: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp4_ra2: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp4_ra3: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mhm1_ra1: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mhm1_ra2: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mhm1_ra3: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_flac: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize: Option<u64>,
     pub gain: Option<String>,
     pub media_version: String,
-    pub disk_number: String,
-    pub track_number: String,
+    #[serde(default, with = "fields::number")]
+    pub disk_number: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub track_number: Option<u64>,
     pub track_token: String,
     pub track_token_expire: i64,
     pub version: Option<String>,
     pub media: Vec<Media>,
-    pub explicit_lyrics: String,
+    #[serde(default, with = "fields::flag")]
+    pub explicit_lyrics: Option<bool>,
     pub rights: Rights,
-    pub isrc: String,
+    pub isrc: ids::Isrc,
     pub hierarchical_title: Option<String>,
     pub sng_contributors: Option<SngContributors>,
     pub lyrics_id: i64,
     pub explicit_track_content: ExplicitContent,
-    pub genre_id: Option<String>,
+    #[serde(default, with = "fields::number")]
+    pub genre_id: Option<GenreId>,
     pub copyright: Option<String>,
-    pub physical_release_date: String,
+    #[serde(default, with = "fields::date")]
+    pub physical_release_date: Option<Date>,
     pub s_mod: Option<i64>,
     pub s_premium: Option<i64>,
     pub date_start_premium: Option<String>,
@@ -92,48 +108,68 @@ pub struct Track {
     pub __type__: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Song {
-    pub alb_id: String,
+    #[serde(with = "fields::id")]
+    pub alb_id: AlbumId,
     pub alb_picture: String,
     pub alb_title: String,
     pub artists: Vec<Artist>,
-    pub art_id: String,
+    #[serde(with = "fields::id")]
+    pub art_id: ArtistId,
     pub art_name: String,
     pub artist_is_dummy: bool,
-    pub digital_release_date: String,
-    pub disk_number: String,
-    pub duration: String,
-    pub explicit_lyrics: String,
+    #[serde(default, with = "fields::date")]
+    pub digital_release_date: Option<Date>,
+    #[serde(default, with = "fields::number")]
+    pub disk_number: Option<u64>,
+    #[serde(default, with = "fields::seconds")]
+    pub duration: Option<Duration>,
+    #[serde(default, with = "fields::flag")]
+    pub explicit_lyrics: Option<bool>,
     pub explicit_track_content: ExplicitContent,
-    #[serde(skip_serializing)]
     pub fallback: Option<Value>,
-    pub genre_id: String,
+    #[serde(with = "fields::id")]
+    pub genre_id: GenreId,
     pub hierarchical_title: String,
-    pub isrc: String,
+    pub isrc: ids::Isrc,
     pub lyrics_id: i64,
-    pub physical_release_date: String,
+    #[serde(default, with = "fields::date")]
+    pub physical_release_date: Option<Date>,
     pub provider_id: String,
-    pub rank: String,
+    #[serde(default, with = "fields::number")]
+    pub rank: Option<u64>,
     pub smartradio: i32,
     pub sng_contributors: SngContributors,
-    pub sng_id: String,
+    #[serde(with = "fields::id")]
+    pub sng_id: TrackId,
     pub sng_title: String,
     pub status: i32,
-    pub track_number: String,
+    #[serde(default, with = "fields::number")]
+    pub track_number: Option<u64>,
     pub user_id: i64,
     pub version: Option<String>,
-    pub filesize_aac_64: String,
-    pub filesize_mp3_64: String,
-    pub filesize_mp3_128: String,
-    pub filesize_mp3_256: String,
-    pub filesize_mp3_320: String,
-    pub filesize_mp4_ra1: String,
-    pub filesize_mp4_ra2: String,
-    pub filesize_mp4_ra3: String,
-    pub filesize_flac: String,
-    pub filesize: String,
+    #[serde(default, with = "fields::number")]
+    pub filesize_aac_64: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_64: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_128: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_256: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp3_320: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp4_ra1: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp4_ra2: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_mp4_ra3: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize_flac: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub filesize: Option<u64>,
     pub gain: Option<String>,
     pub media_version: String,
     pub track_token: String,
@@ -143,16 +179,47 @@ pub struct Song {
     pub __type__: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct SongListData {
     #[serde(rename = "data")]
     pub songs: Option<Vec<Track>>,
     pub count: i64,
     pub total: i64,
     pub filtered_count: i64,
+    // requested ids deezer returned no song for, filled by gw_songs
+    #[serde(skip)]
+    pub missing_ids: Vec<TrackId>,
+    // songs not available in the country of the client filter
+    #[serde(skip)]
+    pub unavailable_ids: Vec<TrackId>,
+}
+
+impl SongListData {
+    // Songs are put back in the order of ids, a song requested twice is returned twice
+    pub(crate) fn merge(parts: Vec<SongListData>, ids: &[TrackId]) -> SongListData {
+        let mut merged = SongListData::default();
+        let mut by_id: HashMap<TrackId, Track> = HashMap::new();
+        for part in parts {
+            merged.count += part.count;
+            merged.total += part.total;
+            merged.filtered_count += part.filtered_count;
+            for song in part.songs.unwrap_or_default() {
+                by_id.entry(song.sng_id).or_insert(song);
+            }
+        }
+        let mut songs = Vec::new();
+        for id in ids {
+            match by_id.get(id) {
+                Some(song) => songs.push(song.clone()),
+                None => merged.missing_ids.push(*id),
+            }
+        }
+        merged.songs = Some(songs);
+        merged
+    }
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Rights {
     pub stream_ads_available: Option<bool>,
@@ -161,21 +228,21 @@ pub struct Rights {
     pub stream_sub: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct ExplicitContent {
     pub explicit_lyrics_status: i32,
     pub explicit_cover_status: i32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(untagged)]
 pub enum Locales {
     Locale(HashMap<String, HashMap<String, String>>),
     Empty(Vec<String>),
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Media {
     #[serde(rename = "TYPE")]
@@ -183,7 +250,7 @@ pub struct Media {
     pub href: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Lyrics {
     pub lyrics_id: String,
@@ -193,7 +260,7 @@ pub struct Lyrics {
     pub lyrics_writers: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct LyricsSyncItem {
     pub lrc_timestamp: Option<String>,
     pub milliseconds: Option<String>,
@@ -201,68 +268,77 @@ pub struct LyricsSyncItem {
     pub line: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Isrc {
     pub data: Vec<IsrcData>,
     pub count: i64,
     pub total: i64,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct IsrcData {
     pub art_name: String,
-    pub art_id: String,
+    #[serde(with = "fields::id")]
+    pub art_id: ArtistId,
     pub alb_picture: String,
-    pub alb_id: String,
+    #[serde(with = "fields::id")]
+    pub alb_id: AlbumId,
     pub alb_title: String,
-    pub duration: String,
-    pub digital_release_date: String,
+    #[serde(default, with = "fields::seconds")]
+    pub duration: Option<Duration>,
+    #[serde(default, with = "fields::date")]
+    pub digital_release_date: Option<Date>,
     pub rights: Rights,
     pub lyrics_id: i64,
     pub __type__: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct RelatedAlbums {
     pub data: Vec<RelatedAlbumsData>,
     pub count: i64,
     pub total: i64,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct RelatedAlbumsData {
     pub art_name: String,
-    pub art_id: String,
+    #[serde(with = "fields::id")]
+    pub art_id: ArtistId,
     pub alb_picture: String,
-    pub alb_id: String,
+    #[serde(with = "fields::id")]
+    pub alb_id: AlbumId,
     pub alb_title: String,
-    pub duration: String,
-    pub digital_release_date: String,
+    #[serde(default, with = "fields::seconds")]
+    pub duration: Option<Duration>,
+    #[serde(default, with = "fields::date")]
+    pub digital_release_date: Option<Date>,
     pub rights: Rights,
     pub lyrics_id: i64,
     pub __type__: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(untagged)]
 pub enum SngContributors {
     SngContributors(HashMap<String, Vec<String>>),
     Empty(Vec<String>),
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct AvailableCountries {
     pub stream_ads: Vec<String>,
     pub stream_sub_only: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Artist {
-    pub art_id: String,
+    #[serde(with = "fields::id")]
+    pub art_id: ArtistId,
     pub role_id: String,
     pub artists_songs_order: String,
     pub art_name: String,
@@ -273,24 +349,32 @@ pub struct Artist {
     pub __type__: String,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub struct Album {
-    pub alb_id: String,
+    #[serde(with = "fields::id")]
+    pub alb_id: AlbumId,
     pub alb_picture: String,
     pub explicit_album_content: ExplicitContent,
     pub alb_title: String,
-    pub art_id: String,
+    #[serde(with = "fields::id")]
+    pub art_id: ArtistId,
     pub art_name: String,
     pub copyright: String,
-    pub digital_release_date: String,
-    pub genre_id: String,
+    #[serde(default, with = "fields::date")]
+    pub digital_release_date: Option<Date>,
+    #[serde(with = "fields::id")]
+    pub genre_id: GenreId,
     pub label_name: String,
     pub nb_fan: i32,
-    pub number_disk: String,
-    pub number_track: String,
-    pub physical_release_date: String,
-    pub original_release_date: Option<String>,
+    #[serde(default, with = "fields::number")]
+    pub number_disk: Option<u64>,
+    #[serde(default, with = "fields::number")]
+    pub number_track: Option<u64>,
+    #[serde(default, with = "fields::date")]
+    pub physical_release_date: Option<Date>,
+    #[serde(default, with = "fields::date")]
+    pub original_release_date: Option<Date>,
     pub rank: String,
     pub rank_art: String,
     pub status: String,
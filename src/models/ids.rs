@@ -0,0 +1,181 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    InvalidId(String),
+    InvalidIsrc(String),
+    InvalidUpc(String),
+}
+
+impl fmt::Display for IdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdError::InvalidId(id) => write!(f, "invalid id {id:?}"),
+            IdError::InvalidIsrc(isrc) => write!(f, "invalid isrc {isrc:?}"),
+            IdError::InvalidUpc(upc) => write!(f, "invalid upc {upc:?}"),
+        }
+    }
+}
+
+// The public api sends ids as numbers and gw as strings, both are accepted
+struct IdVisitor;
+
+impl<'de> Visitor<'de> for IdVisitor {
+    type Value = i64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an id as a number or a string")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+        i64::try_from(value).map_err(|_| E::custom(IdError::InvalidId(value.to_string())))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| E::custom(IdError::InvalidId(value.to_string())))
+    }
+}
+
+macro_rules! numeric_id {
+    ($name:ident, $inner:ty) => {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub $inner);
+
+        impl From<$inner> for $name {
+            fn from(id: $inner) -> Self {
+                $name(id)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(id: &str) -> Result<Self, Self::Err> {
+                id.trim()
+                    .parse()
+                    .map($name)
+                    .map_err(|_| IdError::InvalidId(id.to_string()))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let id = deserializer.deserialize_any(IdVisitor)?;
+                <$inner>::try_from(id)
+                    .map($name)
+                    .map_err(|_| de::Error::custom(IdError::InvalidId(id.to_string())))
+            }
+        }
+    };
+}
+
+numeric_id!(TrackId, u64);
+numeric_id!(AlbumId, u64);
+numeric_id!(ArtistId, u64);
+numeric_id!(PlaylistId, u64);
+// the api uses -1 for albums without genre
+numeric_id!(GenreId, i64);
+
+// International Standard Recording Code, e.g. GBDUW0000053
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Isrc(String);
+
+impl Isrc {
+    // Accepts lowercase codes and the hyphenated form, e.g. gb-duw-00-00053
+    pub fn new(isrc: &str) -> Result<Self, IdError> {
+        let code: String = isrc
+            .trim()
+            .chars()
+            .filter(|c| *c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let bytes = code.as_bytes();
+        let valid = bytes.len() == 12
+            && bytes[..2].iter().all(u8::is_ascii_alphabetic)
+            && bytes[2..5].iter().all(u8::is_ascii_alphanumeric)
+            && bytes[5..].iter().all(u8::is_ascii_digit);
+        match valid {
+            true => Ok(Isrc(code)),
+            false => Err(IdError::InvalidIsrc(isrc.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// Universal Product Code or EAN of an album, kept as text for its leading zeros
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Upc(String);
+
+impl Upc {
+    pub fn new(upc: &str) -> Result<Self, IdError> {
+        let code = upc.trim();
+        match (8..=14).contains(&code.len()) && code.bytes().all(|b| b.is_ascii_digit()) {
+            true => Ok(Upc(code.to_string())),
+            false => Err(IdError::InvalidUpc(upc.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! code_id {
+    ($name:ident) => {
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(code: &str) -> Result<Self, Self::Err> {
+                $name::new(code)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
+
+        // Kept as deezer sent it, tracks without code have an empty one
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map($name)
+            }
+        }
+    };
+}
+
+code_id!(Isrc);
+code_id!(Upc);
@@ -1,2 +1,6 @@
 pub mod api;
+pub mod domain;
+pub mod export;
+pub mod fields;
 pub mod gw;
+pub mod ids;
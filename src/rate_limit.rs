@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::metrics::{QUOTA_LIMIT, QUOTA_WINDOW};
+
+// At most limit requests in any window, shared by the clones of a client
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    sent: Arc<Mutex<VecDeque<Instant>>>,
+    limit: usize,
+    window: Duration,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(QUOTA_LIMIT, QUOTA_WINDOW)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            sent: Arc::default(),
+            limit: limit.max(1),
+            window,
+        }
+    }
+
+    // Waits until one more request fits in the window, then counts it as sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+                let now = Instant::now();
+                while sent
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= self.window)
+                {
+                    sent.pop_front();
+                }
+                match sent.front() {
+                    Some(oldest) if sent.len() >= self.limit => *oldest + self.window - now,
+                    _ => {
+                        sent.push_back(now);
+                        return;
+                    }
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+type Flight<T, E> = Arc<OnceCell<Result<T, Arc<E>>>>;
+
+// Errors handed to the callers waiting on a failed flight, the caller which ran fetch
+// gets the original
+pub(crate) trait ShareError {
+    fn share(&self) -> Self;
+}
+
+impl<E: Clone> ShareError for E {
+    fn share(&self) -> Self {
+        self.clone()
+    }
+}
+
+// Concurrent calls with the same key share the result of the first one still in flight
+#[derive(Debug)]
+pub(crate) struct SingleFlight<T, E> {
+    flights: Arc<Mutex<HashMap<String, Flight<T, E>>>>,
+}
+
+impl<T, E> Clone for SingleFlight<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            flights: self.flights.clone(),
+        }
+    }
+}
+
+impl<T, E> Default for SingleFlight<T, E> {
+    fn default() -> Self {
+        Self {
+            flights: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+// Held by every caller of run, the last one to leave an unfinished flight removes it,
+// so a flight whose callers were all dropped does not stay in the map
+struct Passenger<'a, T, E> {
+    flights: &'a SingleFlight<T, E>,
+    key: &'a str,
+    flight: Flight<T, E>,
+}
+
+impl<T, E> Drop for Passenger<'_, T, E> {
+    fn drop(&mut self) {
+        let mut flights = self
+            .flights
+            .flights
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let current = flights
+            .get(self.key)
+            .is_some_and(|f| Arc::ptr_eq(f, &self.flight));
+        // the map holds the other reference
+        let last = Arc::strong_count(&self.flight) == 2;
+        if current && (self.flight.initialized() || last) {
+            flights.remove(self.key);
+        }
+    }
+}
+
+impl<T: Clone, E: ShareError> SingleFlight<T, E> {
+    fn flights(&self) -> std::sync::MutexGuard<'_, HashMap<String, Flight<T, E>>> {
+        self.flights.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.flights().len()
+    }
+
+    // Errors are shared too, so a failing key is fetched once however many callers wait
+    pub async fn run<F, Fut>(&self, key: &str, fetch: F) -> Result<T, E>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let passenger = Passenger {
+            flights: self,
+            key,
+            flight: self.flights().entry(key.to_string()).or_default().clone(),
+        };
+        let mut error = None;
+        let shared = passenger
+            .flight
+            .get_or_init(|| async {
+                fetch().await.map_err(|err| {
+                    let shared = Arc::new(err.share());
+                    error = Some(err);
+                    shared
+                })
+            })
+            .await
+            .clone();
+        drop(passenger);
+        if let Some(err) = error {
+            return Err(err);
+        }
+        shared.map_err(|err| E::share(&err))
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::Path;
+
+use super::vorbis::{self, Comments};
+use super::{TagError, TagSet};
+
+const FLAC_MARKER: &[u8; 4] = b"fLaC";
+const STREAMINFO: u8 = 0;
+const PADDING: u8 = 1;
+const VORBIS_COMMENT: u8 = 4;
+const PICTURE: u8 = 6;
+
+struct Block {
+    kind: u8,
+    data: Vec<u8>,
+}
+
+fn parse(file: &[u8]) -> Result<(Vec<Block>, usize), TagError> {
+    if file.get(0..4) != Some(FLAC_MARKER) {
+        return Err(TagError::MalformedFile("missing fLaC marker".to_string()));
+    }
+    let mut blocks = Vec::new();
+    let mut pos = 4;
+    loop {
+        let header = file
+            .get(pos..pos + 4)
+            .ok_or_else(|| TagError::MalformedFile("truncated metadata block".to_string()))?;
+        let last = header[0] & 0x80 != 0;
+        let kind = header[0] & 0x7f;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let data = file
+            .get(pos + 4..pos + 4 + len)
+            .ok_or_else(|| TagError::MalformedFile("truncated metadata block".to_string()))?;
+        blocks.push(Block {
+            kind,
+            data: data.to_vec(),
+        });
+        pos += 4 + len;
+        if last {
+            break;
+        }
+    }
+    Ok((blocks, pos))
+}
+
+fn duration(streaminfo: &[u8]) -> Option<u64> {
+    let info = streaminfo.get(10..18)?;
+    let sample_rate =
+        (u64::from(info[0]) << 12) | (u64::from(info[1]) << 4) | (u64::from(info[2]) >> 4);
+    let samples = (u64::from(info[3] & 0x0f) << 32)
+        | u64::from(u32::from_be_bytes([info[4], info[5], info[6], info[7]]));
+    match sample_rate {
+        0 => None,
+        rate => Some(samples / rate),
+    }
+}
+
+pub(super) fn read(path: &Path) -> Result<TagSet, TagError> {
+    let file = fs::read(path)?;
+    let (blocks, _) = parse(&file)?;
+    let mut tags = TagSet::default();
+    for block in blocks.iter() {
+        match block.kind {
+            VORBIS_COMMENT => {
+                let cover = tags.cover.take();
+                tags = vorbis::tags_from_comments(&vorbis::decode(&block.data)?);
+                tags.cover = cover;
+            }
+            PICTURE if tags.cover.is_none() => {
+                tags.cover = Some(vorbis::decode_picture(&block.data)?);
+            }
+            _ => (),
+        }
+    }
+    if let Some(streaminfo) = blocks.iter().find(|b| b.kind == STREAMINFO) {
+        tags.duration = duration(&streaminfo.data);
+    }
+    Ok(tags)
+}
+
+pub(super) fn write(path: &Path, tags: &TagSet) -> Result<(), TagError> {
+    let file = fs::read(path)?;
+    let (blocks, audio_start) = parse(&file)?;
+    let mut existing: Option<Comments> = None;
+    let mut kept: Vec<Block> = Vec::new();
+    for block in blocks {
+        match block.kind {
+            VORBIS_COMMENT => existing = Some(vorbis::decode(&block.data)?),
+            PICTURE if tags.cover.is_some() => (),
+            PADDING => (),
+            _ => kept.push(block),
+        }
+    }
+    let comments = vorbis::merge(existing, tags);
+    // STREAMINFO must stay the first block
+    kept.insert(
+        1.min(kept.len()),
+        Block {
+            kind: VORBIS_COMMENT,
+            data: vorbis::encode(&comments),
+        },
+    );
+    if let Some(cover) = &tags.cover {
+        kept.push(Block {
+            kind: PICTURE,
+            data: vorbis::encode_picture(cover),
+        });
+    }
+
+    let mut output = Vec::with_capacity(file.len());
+    output.extend(FLAC_MARKER);
+    let count = kept.len();
+    for (index, block) in kept.iter().enumerate() {
+        let len = block.data.len();
+        if len >= 1 << 24 {
+            return Err(TagError::MalformedFile(
+                "metadata block too large".to_string(),
+            ));
+        }
+        let last = if index + 1 == count { 0x80 } else { 0 };
+        output.push(last | block.kind);
+        output.extend(&(len as u32).to_be_bytes()[1..]);
+        output.extend(&block.data);
+    }
+    output.extend(&file[audio_start..]);
+    fs::write(path, output)?;
+    Ok(())
+}
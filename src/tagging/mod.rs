@@ -0,0 +1,348 @@
+use std::io::Error as IoError;
+use std::path::Path;
+
+use id3::Error as Id3Error;
+use ogg::OggReadError;
+
+use crate::lyrics::SyncedLyrics;
+use crate::models;
+
+mod flac;
+mod mp3;
+mod mp4;
+mod ogg_vorbis;
+mod vorbis;
+
+pub use mp3::Id3Version;
+
+const COVER_URL: &str = "https://e-cdns-images.dzcdn.net/images/cover/";
+const COVER_SIZE: &str = "1000x1000-000000-80-0-0.jpg";
+
+#[derive(Debug)]
+pub enum TagError {
+    IoError(IoError),
+    Id3Error(Id3Error),
+    OggError(OggReadError),
+    UnsupportedFormat(String),
+    MalformedFile(String),
+}
+
+impl From<IoError> for TagError {
+    fn from(error: IoError) -> Self {
+        TagError::IoError(error)
+    }
+}
+
+impl From<Id3Error> for TagError {
+    fn from(error: Id3Error) -> Self {
+        TagError::Id3Error(error)
+    }
+}
+
+impl From<OggReadError> for TagError {
+    fn from(error: OggReadError) -> Self {
+        TagError::OggError(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Mp3,
+    Flac,
+    Ogg,
+    M4a,
+}
+
+impl AudioFormat {
+    pub fn from_path(path: &Path) -> Option<AudioFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "mp3" => Some(AudioFormat::Mp3),
+            "flac" => Some(AudioFormat::Flac),
+            "ogg" | "oga" | "opus" => Some(AudioFormat::Ogg),
+            "m4a" | "m4b" | "mp4" => Some(AudioFormat::M4a),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picture {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Picture {
+    pub fn jpeg(data: Vec<u8>) -> Self {
+        Self {
+            mime_type: String::from("image/jpeg"),
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagSet {
+    pub title: Option<String>,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub track_number: Option<u32>,
+    pub track_total: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub disc_total: Option<u32>,
+    pub isrc: Option<String>,
+    pub barcode: Option<String>,
+    pub label: Option<String>,
+    pub copyright: Option<String>,
+    pub genres: Vec<String>,
+    pub release_date: Option<String>,
+    pub bpm: Option<f32>,
+    pub gain: Option<f32>,
+    pub duration: Option<u64>,
+    pub explicit: Option<bool>,
+    // (role, name) pairs, roles as reported by deezer e.g. "composer", "Featured"
+    pub contributors: Vec<(String, String)>,
+    pub cover_url: Option<String>,
+    pub cover: Option<Picture>,
+    pub lyrics: Option<String>,
+    // (milliseconds, line) pairs in playback order
+    pub synced_lyrics: Vec<(u32, String)>,
+    pub lyrics_copyright: Option<String>,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    match value.trim() {
+        "" => None,
+        v => Some(v.to_string()),
+    }
+}
+
+fn non_zero(value: u64) -> Option<u32> {
+    match value {
+        0 => None,
+        v => u32::try_from(v).ok(),
+    }
+}
+
+pub(crate) fn cover_url_from_md5(md5: &str) -> Option<String> {
+    non_empty(md5).map(|md5| format!("{COVER_URL}{md5}/{COVER_SIZE}"))
+}
+
+impl TagSet {
+    pub fn artist(&self) -> Option<String> {
+        match self.artists.is_empty() {
+            true => None,
+            false => Some(self.artists.join(", ")),
+        }
+    }
+
+    pub fn contributors_with_role(&self, role: &str) -> Vec<&str> {
+        self.contributors
+            .iter()
+            .filter(|(r, _)| r.eq_ignore_ascii_case(role))
+            .map(|(_, name)| name.as_str())
+            .collect()
+    }
+
+    pub fn year(&self) -> Option<i32> {
+        self.release_date.as_ref()?.get(0..4)?.parse().ok()
+    }
+
+    pub fn with_cover(mut self, cover: Picture) -> Self {
+        self.cover = Some(cover);
+        self
+    }
+
+    // Fills album level fields, values already present on the track are kept
+    pub fn with_album(mut self, album: &models::api::MainAlbum) -> Self {
+        let other = TagSet::from(album);
+        self.album = self.album.or(other.album);
+        self.album_artist = self.album_artist.or(other.album_artist);
+        self.track_total = self.track_total.or(other.track_total);
+        self.barcode = self.barcode.or(other.barcode);
+        self.label = self.label.or(other.label);
+        self.release_date = self.release_date.or(other.release_date);
+        self.cover_url = self.cover_url.or(other.cover_url);
+        if self.genres.is_empty() {
+            self.genres = other.genres;
+        }
+        self
+    }
+
+    // Writers are added as "author" contributors, so they end up in the lyricist tags
+    pub fn with_lyrics(mut self, lyrics: &models::gw::Lyrics) -> Self {
+        self.lyrics = non_empty(&lyrics.lyrics_text);
+        self.synced_lyrics = SyncedLyrics::from(lyrics)
+            .lines
+            .into_iter()
+            .map(|line| (line.start as u32, line.text))
+            .collect();
+        self.lyrics_copyright = non_empty(&lyrics.lyrics_copyrights);
+        for writer in lyrics.lyrics_writers.split([',', '/']) {
+            if let Some(writer) = non_empty(writer) {
+                let contributor = (String::from("author"), writer);
+                if !self.contributors.contains(&contributor) {
+                    self.contributors.push(contributor);
+                }
+            }
+        }
+        self
+    }
+
+    // Fills the fields only the gw layer knows about, such as copyright and composers
+    pub fn with_gw_track(mut self, track: &models::gw::Track) -> Self {
+        let other = TagSet::from(track);
+        self.copyright = self.copyright.or(other.copyright);
+        self.disc_number = self.disc_number.or(other.disc_number);
+        self.track_number = self.track_number.or(other.track_number);
+        self.gain = self.gain.or(other.gain);
+        for contributor in other.contributors {
+            if !self.contributors.contains(&contributor) {
+                self.contributors.push(contributor);
+            }
+        }
+        self
+    }
+}
+
+impl From<&models::api::MainTrack> for TagSet {
+    fn from(track: &models::api::MainTrack) -> Self {
+        let mut artists: Vec<String> = track
+            .contributors
+            .iter()
+            .filter(|c| c.role.eq_ignore_ascii_case("main"))
+            .map(|c| c.name.clone())
+            .collect();
+        if artists.is_empty() {
+            artists.push(track.artist.name.clone());
+        }
+        Self {
+            title: non_empty(&track.title),
+            artists,
+            album: non_empty(&track.album.title),
+            // the track artist is not the album artist on compilations, with_album fills it
+            track_number: non_zero(track.track_position),
+            disc_number: non_zero(track.disk_number),
+            isrc: non_empty(track.isrc.as_str()),
+            release_date: non_empty(&track.release_date),
+            bpm: Some(track.bpm).filter(|bpm| *bpm > 0.0),
+            gain: Some(track.gain).filter(|gain| *gain != 0.0),
+            duration: Some(track.duration).filter(|d| *d > 0),
+            explicit: Some(track.explicit_lyrics),
+            contributors: track
+                .contributors
+                .iter()
+                .map(|c| (c.role.clone(), c.name.clone()))
+                .collect(),
+            cover_url: track
+                .album
+                .cover_xl
+                .clone()
+                .or_else(|| cover_url_from_md5(&track.album.md5_image)),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&models::api::MainAlbum> for TagSet {
+    fn from(album: &models::api::MainAlbum) -> Self {
+        Self {
+            album: non_empty(&album.title),
+            album_artist: non_empty(&album.artist.name),
+            track_total: non_zero(album.nb_tracks),
+            barcode: non_empty(album.upc.as_str()),
+            label: non_empty(&album.label),
+            genres: album.genres.data.iter().map(|g| g.name.clone()).collect(),
+            release_date: non_empty(&album.release_date),
+            explicit: Some(album.explicit_lyrics),
+            cover_url: non_empty(&album.cover_xl).or_else(|| cover_url_from_md5(&album.md5_image)),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&models::gw::Track> for TagSet {
+    fn from(track: &models::gw::Track) -> Self {
+        let title = match track.version.as_deref().and_then(non_empty) {
+            Some(version) => format!("{} {}", track.sng_title, version),
+            None => track.sng_title.clone(),
+        };
+        let mut artists: Vec<String> = track
+            .artists
+            .iter()
+            .filter(|a| a.role_id == "0")
+            .map(|a| a.art_name.clone())
+            .collect();
+        if artists.is_empty() {
+            artists.push(track.art_name.clone());
+        }
+        let mut contributors = Vec::new();
+        if let Some(models::gw::SngContributors::SngContributors(roles)) = &track.sng_contributors {
+            let mut roles: Vec<_> = roles.iter().collect();
+            roles.sort_by_key(|(role, _)| role.as_str());
+            for (role, names) in roles {
+                for name in names {
+                    contributors.push((role.clone(), name.clone()));
+                }
+            }
+        }
+        let release_date = track
+            .physical_release_date
+            .or(track.digital_release_date)
+            .map(|date| date.to_string());
+        Self {
+            title: non_empty(&title),
+            artists,
+            album: non_empty(&track.alb_title),
+            album_artist: non_empty(&track.art_name),
+            track_number: track
+                .track_number
+                .and_then(|n| n.try_into().ok())
+                .filter(|n| *n > 0),
+            disc_number: track
+                .disk_number
+                .and_then(|n| n.try_into().ok())
+                .filter(|n| *n > 0),
+            isrc: non_empty(track.isrc.as_str()),
+            copyright: track.copyright.as_deref().and_then(non_empty),
+            release_date,
+            gain: track.gain.as_deref().and_then(|g| g.parse().ok()),
+            duration: track.duration.map(|d| d.as_secs()).filter(|d| *d > 0),
+            explicit: Some(track.explicit_lyrics == Some(true)),
+            contributors,
+            cover_url: cover_url_from_md5(&track.alb_picture),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn write_tags(path: impl AsRef<Path>, tags: &TagSet) -> Result<(), TagError> {
+    let path = path.as_ref();
+    match AudioFormat::from_path(path) {
+        Some(AudioFormat::Mp3) => mp3::write(path, tags, Id3Version::Id3v24),
+        Some(AudioFormat::Flac) => flac::write(path, tags),
+        Some(AudioFormat::Ogg) => ogg_vorbis::write(path, tags),
+        Some(AudioFormat::M4a) => mp4::write(path, tags),
+        None => Err(TagError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+pub fn write_id3(
+    path: impl AsRef<Path>,
+    tags: &TagSet,
+    version: Id3Version,
+) -> Result<(), TagError> {
+    mp3::write(path.as_ref(), tags, version)
+}
+
+pub fn read_tags(path: impl AsRef<Path>) -> Result<TagSet, TagError> {
+    let path = path.as_ref();
+    match AudioFormat::from_path(path) {
+        Some(AudioFormat::Mp3) => mp3::read(path),
+        Some(AudioFormat::Flac) => flac::read(path),
+        Some(AudioFormat::Ogg) => ogg_vorbis::read(path),
+        Some(AudioFormat::M4a) => mp4::read(path),
+        None => Err(TagError::UnsupportedFormat(path.display().to_string())),
+    }
+}
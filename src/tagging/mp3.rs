@@ -0,0 +1,253 @@
+use std::path::Path;
+
+use id3::frame::{
+    Content, ExtendedText, InvolvedPeopleList, InvolvedPeopleListItem, Lyrics, Picture,
+    PictureType, SynchronisedLyrics, SynchronisedLyricsType, TimestampFormat,
+};
+use id3::{ErrorKind, Frame, Tag, TagLike, Version};
+
+use super::{TagError, TagSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Id3Version {
+    Id3v23,
+    Id3v24,
+}
+
+const GAIN_DESCRIPTION: &str = "REPLAYGAIN_TRACK_GAIN";
+const BARCODE_DESCRIPTION: &str = "BARCODE";
+const LYRICS_COPYRIGHT_DESCRIPTION: &str = "LYRICS_COPYRIGHT";
+// iTunes advisory, as written to vorbis comments, 1 for explicit and 0 for clean
+const EXPLICIT_DESCRIPTION: &str = "ITUNESADVISORY";
+// ID3 code for an undetermined language, deezer does not report one for lyrics
+const LYRICS_LANGUAGE: &str = "XXX";
+
+fn read_or_new(path: &Path) -> Result<Tag, TagError> {
+    match Tag::read_from_path(path) {
+        Ok(tag) => Ok(tag),
+        Err(error) if matches!(error.kind, ErrorKind::NoTag) => Ok(Tag::new()),
+        Err(error) => Err(TagError::Id3Error(error)),
+    }
+}
+
+fn pair(number: Option<u32>, total: Option<u32>) -> Option<String> {
+    match (number, total) {
+        (Some(number), Some(total)) => Some(format!("{number}/{total}")),
+        (Some(number), None) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn set_extended(tag: &mut Tag, description: &str, value: Option<String>) {
+    if let Some(value) = value {
+        tag.remove_extended_text(Some(description), None);
+        tag.add_frame(ExtendedText {
+            description: description.to_string(),
+            value,
+        });
+    }
+}
+
+// Only the frames the tag set has a value for are replaced, anything else in an
+// existing tag is preserved
+pub(super) fn write(path: &Path, tags: &TagSet, version: Id3Version) -> Result<(), TagError> {
+    let mut tag = read_or_new(path)?;
+    if tags.release_date.is_some() {
+        for id in ["TDRC", "TYER", "TDAT"] {
+            tag.remove(id);
+        }
+    }
+    let mut set = |id: &str, value: Option<String>| {
+        if let Some(value) = value {
+            tag.remove(id);
+            tag.set_text(id, value);
+        }
+    };
+    set("TIT2", tags.title.clone());
+    set("TALB", tags.album.clone());
+    set("TPE2", tags.album_artist.clone());
+    set("TRCK", pair(tags.track_number, tags.track_total));
+    set("TPOS", pair(tags.disc_number, tags.disc_total));
+    set("TSRC", tags.isrc.clone());
+    set("TPUB", tags.label.clone());
+    set("TCOP", tags.copyright.clone());
+    set("TBPM", tags.bpm.map(|bpm| bpm.round().to_string()));
+    set("TLEN", tags.duration.map(|d| (d * 1000).to_string()));
+    match version {
+        Id3Version::Id3v24 => set("TDRC", tags.release_date.clone()),
+        Id3Version::Id3v23 => {
+            set("TYER", tags.year().map(|year| year.to_string()));
+            let date = tags
+                .release_date
+                .as_ref()
+                .and_then(|date| Some(format!("{}{}", date.get(8..10)?, date.get(5..7)?)));
+            set("TDAT", date);
+        }
+    }
+    // ID3v2.4 separates multiple values with a null byte. v2.3 only has the slash,
+    // which also shows up in names such as "AC/DC", so only the first value is written
+    let join = |values: Vec<&str>| match version {
+        Id3Version::Id3v23 => values.first().map(|value| value.to_string()),
+        Id3Version::Id3v24 => Some(values.join("\0")).filter(|v| !v.is_empty()),
+    };
+    let artists = tags.artists.iter().map(String::as_str).collect();
+    let genres = tags.genres.iter().map(String::as_str).collect();
+    set("TPE1", join(artists));
+    set("TCON", join(genres));
+    set("TCOM", join(tags.contributors_with_role("composer")));
+    set("TEXT", join(tags.contributors_with_role("author")));
+    let involved: Vec<InvolvedPeopleListItem> = tags
+        .contributors
+        .iter()
+        .filter(|(role, _)| {
+            let role = role.to_ascii_lowercase();
+            !matches!(
+                role.as_str(),
+                "main" | "main_artist" | "composer" | "author"
+            )
+        })
+        .map(|(role, name)| InvolvedPeopleListItem {
+            involvement: role.clone(),
+            involvee: name.clone(),
+        })
+        .collect();
+    if !involved.is_empty() {
+        let id = match version {
+            Id3Version::Id3v23 => "IPLS",
+            Id3Version::Id3v24 => "TIPL",
+        };
+        tag.remove("TIPL");
+        tag.remove("IPLS");
+        tag.add_frame(Frame::with_content(
+            id,
+            Content::InvolvedPeopleList(InvolvedPeopleList { items: involved }),
+        ));
+    }
+    let gain = tags.gain.map(|gain| format!("{gain:.2} dB"));
+    set_extended(&mut tag, GAIN_DESCRIPTION, gain);
+    set_extended(&mut tag, BARCODE_DESCRIPTION, tags.barcode.clone());
+    set_extended(
+        &mut tag,
+        LYRICS_COPYRIGHT_DESCRIPTION,
+        tags.lyrics_copyright.clone(),
+    );
+    let explicit = tags.explicit.map(|e| if e { "1" } else { "0" }.to_string());
+    set_extended(&mut tag, EXPLICIT_DESCRIPTION, explicit);
+    if let Some(lyrics) = &tags.lyrics {
+        tag.remove_all_lyrics();
+        tag.add_frame(Lyrics {
+            lang: LYRICS_LANGUAGE.to_string(),
+            description: String::new(),
+            text: lyrics.clone(),
+        });
+    }
+    if !tags.synced_lyrics.is_empty() {
+        tag.remove_all_synchronised_lyrics();
+        tag.add_frame(SynchronisedLyrics {
+            lang: LYRICS_LANGUAGE.to_string(),
+            timestamp_format: TimestampFormat::Ms,
+            content_type: SynchronisedLyricsType::Lyrics,
+            description: String::new(),
+            content: tags.synced_lyrics.clone(),
+        });
+    }
+    if let Some(cover) = &tags.cover {
+        tag.remove_picture_by_type(PictureType::CoverFront);
+        tag.add_frame(Picture {
+            mime_type: cover.mime_type.clone(),
+            picture_type: PictureType::CoverFront,
+            description: String::new(),
+            data: cover.data.clone(),
+        });
+    }
+
+    let version = match version {
+        Id3Version::Id3v23 => Version::Id3v23,
+        Id3Version::Id3v24 => Version::Id3v24,
+    };
+    tag.write_to_path(path, version)?;
+    Ok(())
+}
+
+// id3 splits v2.3 values on every slash, they are joined back as v2.3 tags are
+// written with a single value
+fn split(values: Option<Vec<&str>>, version: Version) -> Vec<String> {
+    let values = values.unwrap_or_default();
+    let values = match version {
+        Version::Id3v24 => values.iter().map(|value| value.to_string()).collect(),
+        _ => vec![values.join("/")],
+    };
+    values
+        .iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+pub(super) fn read(path: &Path) -> Result<TagSet, TagError> {
+    let tag = read_or_new(path)?;
+    let split = |values| split(values, tag.version());
+    let extended = |description: &str| {
+        tag.extended_texts()
+            .find(|t| t.description.eq_ignore_ascii_case(description))
+            .map(|t| t.value.clone())
+    };
+    let mut contributors = Vec::new();
+    for composer in split(tag.text_values_for_frame_id("TCOM")) {
+        contributors.push(("composer".to_string(), composer));
+    }
+    for author in split(tag.text_values_for_frame_id("TEXT")) {
+        contributors.push(("author".to_string(), author));
+    }
+    for list in tag.involved_people_lists() {
+        for item in list.items.iter() {
+            contributors.push((item.involvement.clone(), item.involvee.clone()));
+        }
+    }
+    let release_date = tag
+        .text_for_frame_id("TDRC")
+        .map(str::to_string)
+        .or_else(|| tag.year().map(|year| year.to_string()));
+    let cover = tag
+        .pictures()
+        .find(|p| p.picture_type == PictureType::CoverFront)
+        .map(|p| super::Picture {
+            mime_type: p.mime_type.clone(),
+            data: p.data.clone(),
+        });
+    let lyrics = tag.lyrics().next().map(|lyrics| lyrics.text.clone());
+    let synced_lyrics = tag
+        .synchronised_lyrics()
+        .find(|lyrics| lyrics.timestamp_format == TimestampFormat::Ms)
+        .map(|lyrics| lyrics.content.clone())
+        .unwrap_or_default();
+    Ok(TagSet {
+        title: tag.title().map(str::to_string),
+        artists: split(tag.artists()),
+        album: tag.album().map(str::to_string),
+        album_artist: tag.album_artist().map(str::to_string),
+        track_number: tag.track(),
+        track_total: tag.total_tracks(),
+        disc_number: tag.disc(),
+        disc_total: tag.total_discs(),
+        isrc: tag.text_for_frame_id("TSRC").map(str::to_string),
+        barcode: extended(BARCODE_DESCRIPTION),
+        label: tag.text_for_frame_id("TPUB").map(str::to_string),
+        copyright: tag.text_for_frame_id("TCOP").map(str::to_string),
+        genres: split(tag.genres()),
+        release_date,
+        bpm: tag
+            .text_for_frame_id("TBPM")
+            .and_then(|bpm| bpm.parse().ok()),
+        gain: extended(GAIN_DESCRIPTION)
+            .and_then(|gain| gain.trim_end_matches("dB").trim().parse().ok()),
+        duration: tag.duration().map(|ms| u64::from(ms) / 1000),
+        contributors,
+        cover,
+        lyrics,
+        synced_lyrics,
+        lyrics_copyright: extended(LYRICS_COPYRIGHT_DESCRIPTION),
+        explicit: extended(EXPLICIT_DESCRIPTION).map(|e| e == "1"),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,491 @@
+use std::fs;
+use std::path::Path;
+
+use super::{Picture, TagError, TagSet};
+
+type Kind = [u8; 4];
+
+const MOOV: Kind = *b"moov";
+const MVHD: Kind = *b"mvhd";
+const TRAK: Kind = *b"trak";
+const MDIA: Kind = *b"mdia";
+const MINF: Kind = *b"minf";
+const STBL: Kind = *b"stbl";
+const STCO: Kind = *b"stco";
+const CO64: Kind = *b"co64";
+const UDTA: Kind = *b"udta";
+const META: Kind = *b"meta";
+const HDLR: Kind = *b"hdlr";
+const ILST: Kind = *b"ilst";
+const DATA: Kind = *b"data";
+const MEAN: Kind = *b"mean";
+const NAME: Kind = *b"name";
+const FREEFORM: Kind = *b"----";
+
+const TITLE: Kind = *b"\xa9nam";
+const ARTIST: Kind = *b"\xa9ART";
+const ALBUM: Kind = *b"\xa9alb";
+const ALBUM_ARTIST: Kind = *b"aART";
+const TRACK: Kind = *b"trkn";
+const DISC: Kind = *b"disk";
+const DATE: Kind = *b"\xa9day";
+const GENRE: Kind = *b"\xa9gen";
+const COPYRIGHT: Kind = *b"cprt";
+const COMPOSER: Kind = *b"\xa9wrt";
+const BPM: Kind = *b"tmpo";
+const RATING: Kind = *b"rtng";
+const COVER: Kind = *b"covr";
+const LYRICS: Kind = *b"\xa9lyr";
+
+const TYPE_IMPLICIT: u32 = 0;
+const TYPE_UTF8: u32 = 1;
+const TYPE_JPEG: u32 = 13;
+const TYPE_PNG: u32 = 14;
+const TYPE_INTEGER: u32 = 21;
+
+const ITUNES_MEAN: &str = "com.apple.iTunes";
+const FREEFORM_ISRC: &str = "ISRC";
+const FREEFORM_BARCODE: &str = "BARCODE";
+const FREEFORM_LABEL: &str = "LABEL";
+const FREEFORM_GAIN: &str = "REPLAYGAIN_TRACK_GAIN";
+const FREEFORM_LYRICIST: &str = "LYRICIST";
+const FREEFORM_LYRICS_COPYRIGHT: &str = "LYRICS_COPYRIGHT";
+
+#[derive(Debug, Clone, Copy)]
+struct Atom {
+    kind: Kind,
+    start: usize,
+    content: usize,
+    end: usize,
+}
+
+fn malformed(reason: &str) -> TagError {
+    TagError::MalformedFile(reason.to_string())
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Result<u32, TagError> {
+    let bytes = data
+        .get(pos..pos + 4)
+        .ok_or_else(|| malformed("truncated atom"))?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Result<u64, TagError> {
+    Ok((u64::from(read_u32(data, pos)?) << 32) | u64::from(read_u32(data, pos + 4)?))
+}
+
+fn children(data: &[u8], start: usize, end: usize) -> Result<Vec<Atom>, TagError> {
+    let mut atoms = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= end {
+        let size = read_u32(data, pos)? as usize;
+        let kind: Kind = [data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]];
+        let (content, atom_end) = match size {
+            0 => (pos + 8, end),
+            1 => (pos + 16, pos + read_u64(data, pos + 8)? as usize),
+            size => (pos + 8, pos + size),
+        };
+        if atom_end < content || atom_end > end {
+            return Err(malformed("atom exceeds its parent"));
+        }
+        atoms.push(Atom {
+            kind,
+            start: pos,
+            content,
+            end: atom_end,
+        });
+        pos = atom_end;
+    }
+    Ok(atoms)
+}
+
+fn find(atoms: &[Atom], kind: Kind) -> Option<Atom> {
+    atoms.iter().find(|a| a.kind == kind).copied()
+}
+
+fn atom(kind: Kind, content: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(content.len() + 8);
+    data.extend((content.len() as u32 + 8).to_be_bytes());
+    data.extend(kind);
+    data.extend(content);
+    data
+}
+
+fn data_atom(data_type: u32, payload: &[u8]) -> Vec<u8> {
+    let mut content = Vec::with_capacity(payload.len() + 8);
+    content.extend(data_type.to_be_bytes());
+    content.extend(0u32.to_be_bytes());
+    content.extend(payload);
+    atom(DATA, &content)
+}
+
+fn text_item(kind: Kind, values: &[String]) -> Vec<u8> {
+    let content: Vec<u8> = values
+        .iter()
+        .flat_map(|value| data_atom(TYPE_UTF8, value.as_bytes()))
+        .collect();
+    atom(kind, &content)
+}
+
+fn freeform_item(name: &str, value: &str) -> Vec<u8> {
+    let mut content = Vec::new();
+    content.extend(atom(
+        MEAN,
+        &[&[0u8; 4][..], ITUNES_MEAN.as_bytes()].concat(),
+    ));
+    content.extend(atom(NAME, &[&[0u8; 4][..], name.as_bytes()].concat()));
+    content.extend(data_atom(TYPE_UTF8, value.as_bytes()));
+    atom(FREEFORM, &content)
+}
+
+fn pair_item(kind: Kind, number: u32, total: Option<u32>, trailing: usize) -> Vec<u8> {
+    let mut payload = vec![0u8; 2];
+    payload.extend((number.min(u16::MAX as u32) as u16).to_be_bytes());
+    payload.extend((total.unwrap_or(0).min(u16::MAX as u32) as u16).to_be_bytes());
+    payload.extend(vec![0u8; trailing]);
+    atom(kind, &data_atom(TYPE_IMPLICIT, &payload))
+}
+
+fn freeform_name(data: &[u8], item: &Atom) -> Result<Option<String>, TagError> {
+    let atoms = children(data, item.content, item.end)?;
+    Ok(find(&atoms, NAME).and_then(|name| {
+        let bytes = data.get(name.content + 4..name.end)?;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }))
+}
+
+fn item_values(data: &[u8], item: &Atom) -> Result<Vec<(u32, Vec<u8>)>, TagError> {
+    let mut values = Vec::new();
+    for child in children(data, item.content, item.end)? {
+        if child.kind == DATA && child.end >= child.content + 8 {
+            let data_type = read_u32(data, child.content)? & 0x00ff_ffff;
+            values.push((data_type, data[child.content + 8..child.end].to_vec()));
+        }
+    }
+    Ok(values)
+}
+
+fn build_items(tags: &TagSet) -> Vec<u8> {
+    let mut items = Vec::new();
+    let mut text = |kind: Kind, value: &Option<String>| {
+        if let Some(value) = value {
+            items.extend(text_item(kind, std::slice::from_ref(value)));
+        }
+    };
+    text(TITLE, &tags.title);
+    text(ALBUM, &tags.album);
+    text(ALBUM_ARTIST, &tags.album_artist);
+    text(DATE, &tags.release_date);
+    text(COPYRIGHT, &tags.copyright);
+    text(LYRICS, &tags.lyrics);
+    if !tags.artists.is_empty() {
+        items.extend(text_item(ARTIST, &tags.artists));
+    }
+    if !tags.genres.is_empty() {
+        items.extend(text_item(GENRE, &tags.genres));
+    }
+    let composers: Vec<String> = tags
+        .contributors_with_role("composer")
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    if !composers.is_empty() {
+        items.extend(text_item(COMPOSER, &composers));
+    }
+    if let Some(number) = tags.track_number {
+        items.extend(pair_item(TRACK, number, tags.track_total, 2));
+    }
+    if let Some(number) = tags.disc_number {
+        items.extend(pair_item(DISC, number, tags.disc_total, 0));
+    }
+    if let Some(bpm) = tags.bpm {
+        let bpm = bpm.round().clamp(0.0, u16::MAX as f32) as u16;
+        items.extend(atom(BPM, &data_atom(TYPE_INTEGER, &bpm.to_be_bytes())));
+    }
+    if let Some(explicit) = tags.explicit {
+        // iTunes rates explicit tracks 1 and clean ones 2, 0 means no rating
+        let rating = if explicit { 1 } else { 2 };
+        items.extend(atom(RATING, &data_atom(TYPE_INTEGER, &[rating])));
+    }
+    let mut freeform = |name: &str, value: Option<String>| {
+        if let Some(value) = value {
+            items.extend(freeform_item(name, &value));
+        }
+    };
+    freeform(FREEFORM_ISRC, tags.isrc.clone());
+    freeform(FREEFORM_BARCODE, tags.barcode.clone());
+    freeform(FREEFORM_LABEL, tags.label.clone());
+    freeform(FREEFORM_GAIN, tags.gain.map(|gain| format!("{gain:.2} dB")));
+    for author in tags.contributors_with_role("author") {
+        freeform(FREEFORM_LYRICIST, Some(author.to_string()));
+    }
+    freeform(FREEFORM_LYRICS_COPYRIGHT, tags.lyrics_copyright.clone());
+    if let Some(cover) = &tags.cover {
+        let data_type = match cover.mime_type.as_str() {
+            "image/png" => TYPE_PNG,
+            _ => TYPE_JPEG,
+        };
+        items.extend(atom(COVER, &data_atom(data_type, &cover.data)));
+    }
+    items
+}
+
+fn build_ilst(data: &[u8], existing: Option<Atom>, tags: &TagSet) -> Result<Vec<u8>, TagError> {
+    // only the items the TagSet has a value for are replaced, anything else in
+    // an existing ilst is preserved
+    let items = build_items(tags);
+    let mut kinds = Vec::new();
+    let mut freeform = Vec::new();
+    for item in children(&items, 0, items.len())? {
+        match item.kind {
+            FREEFORM => freeform.extend(freeform_name(&items, &item)?),
+            kind => kinds.push(kind),
+        }
+    }
+    let mut content = Vec::new();
+    if let Some(ilst) = existing {
+        for item in children(data, ilst.content, ilst.end)? {
+            let replaced = match item.kind {
+                FREEFORM => {
+                    freeform_name(data, &item)?.is_some_and(|name| freeform.contains(&name))
+                }
+                kind => kinds.contains(&kind),
+            };
+            if !replaced {
+                content.extend(&data[item.start..item.end]);
+            }
+        }
+    }
+    content.extend(items);
+    Ok(atom(ILST, &content))
+}
+
+fn build_meta(data: &[u8], existing: Option<Atom>, tags: &TagSet) -> Result<Vec<u8>, TagError> {
+    let mut content = vec![0u8; 4];
+    match existing {
+        Some(meta) => {
+            let atoms = children(data, meta.content + 4, meta.end)?;
+            if find(&atoms, HDLR).is_none() {
+                content.extend(handler());
+            }
+            let ilst = find(&atoms, ILST);
+            for child in atoms.iter().filter(|a| a.kind != ILST) {
+                content.extend(&data[child.start..child.end]);
+            }
+            content.extend(build_ilst(data, ilst, tags)?);
+        }
+        None => {
+            content.extend(handler());
+            content.extend(build_ilst(data, None, tags)?);
+        }
+    }
+    Ok(atom(META, &content))
+}
+
+fn handler() -> Vec<u8> {
+    let mut content = vec![0u8; 8];
+    content.extend(b"mdirappl");
+    content.extend([0u8; 9]);
+    atom(HDLR, &content)
+}
+
+fn build_udta(data: &[u8], existing: Option<Atom>, tags: &TagSet) -> Result<Vec<u8>, TagError> {
+    let mut content = Vec::new();
+    let mut meta = None;
+    if let Some(udta) = existing {
+        for child in children(data, udta.content, udta.end)? {
+            match child.kind {
+                META => meta = Some(child),
+                _ => content.extend(&data[child.start..child.end]),
+            }
+        }
+    }
+    content.extend(build_meta(data, meta, tags)?);
+    Ok(atom(UDTA, &content))
+}
+
+// Chunk offsets are absolute, so media stored after moov moves when moov grows or shrinks
+fn shift_chunk_offsets(
+    moov: &mut [u8],
+    start: usize,
+    end: usize,
+    after: u64,
+    delta: i64,
+) -> Result<(), TagError> {
+    for child in children(moov, start, end)? {
+        match child.kind {
+            TRAK | MDIA | MINF | STBL => {
+                shift_chunk_offsets(moov, child.content, child.end, after, delta)?
+            }
+            STCO | CO64 => {
+                let count = read_u32(moov, child.content + 4)? as usize;
+                let width = if child.kind == STCO { 4 } else { 8 };
+                for index in 0..count {
+                    let pos = child.content + 8 + index * width;
+                    let offset = match width {
+                        4 => u64::from(read_u32(moov, pos)?),
+                        _ => read_u64(moov, pos)?,
+                    };
+                    if offset < after {
+                        continue;
+                    }
+                    let shifted = (offset as i64 + delta) as u64;
+                    match width {
+                        4 => {
+                            let shifted = u32::try_from(shifted)
+                                .map_err(|_| malformed("chunk offset overflow"))?;
+                            moov[pos..pos + 4].copy_from_slice(&shifted.to_be_bytes());
+                        }
+                        _ => moov[pos..pos + 8].copy_from_slice(&shifted.to_be_bytes()),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn write(path: &Path, tags: &TagSet) -> Result<(), TagError> {
+    let file = fs::read(path)?;
+    let top = children(&file, 0, file.len())?;
+    let moov = find(&top, MOOV).ok_or_else(|| malformed("missing moov atom"))?;
+
+    let mut content = Vec::new();
+    let mut udta = None;
+    for child in children(&file, moov.content, moov.end)? {
+        match child.kind {
+            UDTA => udta = Some(child),
+            _ => content.extend(&file[child.start..child.end]),
+        }
+    }
+    content.extend(build_udta(&file, udta, tags)?);
+    let mut new_moov = atom(MOOV, &content);
+
+    let delta = new_moov.len() as i64 - (moov.end - moov.start) as i64;
+    if delta != 0 {
+        let len = new_moov.len();
+        shift_chunk_offsets(&mut new_moov, 8, len, moov.end as u64, delta)?;
+    }
+
+    let mut output = Vec::with_capacity(file.len() + new_moov.len());
+    output.extend(&file[..moov.start]);
+    output.extend(new_moov);
+    output.extend(&file[moov.end..]);
+    fs::write(path, output)?;
+    Ok(())
+}
+
+fn duration(data: &[u8], mvhd: &Atom) -> Result<Option<u64>, TagError> {
+    let (timescale, duration) = match data.get(mvhd.content) {
+        Some(1) => (
+            read_u32(data, mvhd.content + 20)?,
+            read_u64(data, mvhd.content + 24)?,
+        ),
+        _ => (
+            read_u32(data, mvhd.content + 12)?,
+            u64::from(read_u32(data, mvhd.content + 16)?),
+        ),
+    };
+    match timescale {
+        0 => Ok(None),
+        timescale => Ok(Some(duration / u64::from(timescale))),
+    }
+}
+
+pub(super) fn read(path: &Path) -> Result<TagSet, TagError> {
+    let file = fs::read(path)?;
+    let top = children(&file, 0, file.len())?;
+    let moov = find(&top, MOOV).ok_or_else(|| malformed("missing moov atom"))?;
+    let moov_children = children(&file, moov.content, moov.end)?;
+    let mut tags = TagSet::default();
+    if let Some(mvhd) = find(&moov_children, MVHD) {
+        tags.duration = duration(&file, &mvhd)?;
+    }
+    let ilst = find(&moov_children, UDTA)
+        .map(|udta| children(&file, udta.content, udta.end))
+        .transpose()?
+        .and_then(|atoms| find(&atoms, META))
+        .map(|meta| children(&file, meta.content + 4, meta.end))
+        .transpose()?
+        .and_then(|atoms| find(&atoms, ILST));
+    let Some(ilst) = ilst else {
+        return Ok(tags);
+    };
+
+    let text = |values: &[(u32, Vec<u8>)]| -> Vec<String> {
+        values
+            .iter()
+            .map(|(_, value)| String::from_utf8_lossy(value).into_owned())
+            .collect()
+    };
+    let pair = |values: &[(u32, Vec<u8>)]| -> (Option<u32>, Option<u32>) {
+        let Some((_, value)) = values.first() else {
+            return (None, None);
+        };
+        let read = |pos: usize| {
+            value
+                .get(pos..pos + 2)
+                .map(|b| u32::from(u16::from_be_bytes([b[0], b[1]])))
+                .filter(|n| *n > 0)
+        };
+        (read(2), read(4))
+    };
+    for item in children(&file, ilst.content, ilst.end)? {
+        let values = item_values(&file, &item)?;
+        let first = text(&values).into_iter().next();
+        match item.kind {
+            TITLE => tags.title = first,
+            ARTIST => tags.artists = text(&values),
+            ALBUM => tags.album = first,
+            ALBUM_ARTIST => tags.album_artist = first,
+            DATE => tags.release_date = first,
+            GENRE => tags.genres = text(&values),
+            COPYRIGHT => tags.copyright = first,
+            LYRICS => tags.lyrics = first,
+            COMPOSER => {
+                for composer in text(&values) {
+                    tags.contributors.push(("composer".to_string(), composer));
+                }
+            }
+            TRACK => (tags.track_number, tags.track_total) = pair(&values),
+            DISC => (tags.disc_number, tags.disc_total) = pair(&values),
+            BPM => {
+                tags.bpm = values.first().and_then(|(_, value)| {
+                    Some(f32::from(u16::from_be_bytes([
+                        *value.first()?,
+                        *value.get(1)?,
+                    ])))
+                })
+            }
+            RATING => tags.explicit = values.first().and_then(|(_, v)| v.first()).map(|r| *r == 1),
+            COVER => {
+                tags.cover = values.first().map(|(data_type, data)| Picture {
+                    mime_type: match *data_type {
+                        TYPE_PNG => "image/png",
+                        _ => "image/jpeg",
+                    }
+                    .to_string(),
+                    data: data.clone(),
+                })
+            }
+            FREEFORM => match freeform_name(&file, &item)?.as_deref() {
+                Some(FREEFORM_ISRC) => tags.isrc = first,
+                Some(FREEFORM_BARCODE) => tags.barcode = first,
+                Some(FREEFORM_LABEL) => tags.label = first,
+                Some(FREEFORM_LYRICS_COPYRIGHT) => tags.lyrics_copyright = first,
+                Some(FREEFORM_GAIN) => {
+                    tags.gain = first.and_then(|g| g.trim_end_matches("dB").trim().parse().ok())
+                }
+                Some(FREEFORM_LYRICIST) => {
+                    if let Some(author) = first {
+                        tags.contributors.push(("author".to_string(), author));
+                    }
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    Ok(tags)
+}
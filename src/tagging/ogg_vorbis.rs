@@ -0,0 +1,142 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ogg::{PacketReader, PacketWriteEndInfo, PacketWriter};
+
+use super::vorbis::{self, Comments};
+use super::{TagError, TagSet};
+
+const VORBIS_IDENT: &[u8] = b"\x01vorbis";
+const VORBIS_COMMENT: &[u8] = b"\x03vorbis";
+const OPUS_IDENT: &[u8] = b"OpusHead";
+const OPUS_COMMENT: &[u8] = b"OpusTags";
+const PICTURE_KEY: &str = "METADATA_BLOCK_PICTURE";
+
+enum Codec {
+    Vorbis { sample_rate: u64 },
+    Opus { pre_skip: u64 },
+}
+
+impl Codec {
+    fn from_ident(packet: &[u8]) -> Result<Codec, TagError> {
+        if packet.starts_with(VORBIS_IDENT) && packet.len() >= 16 {
+            let rate = u32::from_le_bytes([packet[12], packet[13], packet[14], packet[15]]);
+            return Ok(Codec::Vorbis {
+                sample_rate: u64::from(rate),
+            });
+        }
+        if packet.starts_with(OPUS_IDENT) && packet.len() >= 12 {
+            let pre_skip = u16::from_le_bytes([packet[10], packet[11]]);
+            return Ok(Codec::Opus {
+                pre_skip: u64::from(pre_skip),
+            });
+        }
+        Err(TagError::UnsupportedFormat(
+            "ogg stream is neither vorbis nor opus".to_string(),
+        ))
+    }
+
+    fn comment_prefix(&self) -> &'static [u8] {
+        match self {
+            Codec::Vorbis { .. } => VORBIS_COMMENT,
+            Codec::Opus { .. } => OPUS_COMMENT,
+        }
+    }
+
+    fn duration(&self, granule: u64) -> Option<u64> {
+        match self {
+            Codec::Vorbis { sample_rate: 0 } => None,
+            Codec::Vorbis { sample_rate } => Some(granule / sample_rate),
+            Codec::Opus { pre_skip } => Some(granule.saturating_sub(*pre_skip) / 48_000),
+        }
+    }
+
+    fn decode_comments(&self, packet: &[u8]) -> Result<Comments, TagError> {
+        let prefix = self.comment_prefix();
+        match packet.strip_prefix(prefix) {
+            Some(data) => vorbis::decode(data),
+            None => Err(TagError::MalformedFile(
+                "missing comment header".to_string(),
+            )),
+        }
+    }
+
+    fn encode_comments(&self, comments: &Comments) -> Vec<u8> {
+        let mut packet = self.comment_prefix().to_vec();
+        packet.extend(vorbis::encode(comments));
+        if let Codec::Vorbis { .. } = self {
+            // vorbis comment headers end with a framing bit
+            packet.push(1);
+        }
+        packet
+    }
+}
+
+pub(super) fn read(path: &Path) -> Result<TagSet, TagError> {
+    let file = fs::read(path)?;
+    let mut reader = PacketReader::new(Cursor::new(file));
+    let ident = reader.read_packet_expected()?;
+    let codec = Codec::from_ident(&ident.data)?;
+    let comment = reader.read_packet_expected()?;
+    let comments = codec.decode_comments(&comment.data)?;
+    let mut tags = vorbis::tags_from_comments(&comments);
+    if let Some(picture) = comments.get(PICTURE_KEY) {
+        if let Ok(data) = BASE64.decode(picture) {
+            tags.cover = vorbis::decode_picture(&data).ok();
+        }
+    }
+    let mut granule = 0;
+    while let Some(packet) = reader.read_packet()? {
+        if packet.stream_serial() == ident.stream_serial() {
+            granule = packet.absgp_page();
+        }
+    }
+    tags.duration = codec.duration(granule);
+    Ok(tags)
+}
+
+pub(super) fn write(path: &Path, tags: &TagSet) -> Result<(), TagError> {
+    let file = fs::read(path)?;
+    let mut reader = PacketReader::new(Cursor::new(file));
+    let mut writer = PacketWriter::new(Vec::new());
+    let mut codec: Option<Codec> = None;
+    let mut index = 0;
+    while let Some(packet) = reader.read_packet()? {
+        let serial = packet.stream_serial();
+        let end_info = if packet.last_in_stream() {
+            PacketWriteEndInfo::EndStream
+        } else if packet.last_in_page() {
+            PacketWriteEndInfo::EndPage
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        let absgp = packet.absgp_page();
+        let data = match (index, &codec) {
+            (0, _) => {
+                codec = Some(Codec::from_ident(&packet.data)?);
+                packet.data
+            }
+            (1, Some(codec)) => {
+                let existing = codec.decode_comments(&packet.data)?;
+                let mut comments = vorbis::merge(Some(existing), tags);
+                if let Some(cover) = &tags.cover {
+                    comments.comments.retain(|(key, _)| key != PICTURE_KEY);
+                    let picture = BASE64.encode(vorbis::encode_picture(cover));
+                    comments.comments.push((PICTURE_KEY.to_string(), picture));
+                }
+                codec.encode_comments(&comments)
+            }
+            _ => packet.data,
+        };
+        writer.write_packet(data.into_boxed_slice(), serial, end_info, absgp)?;
+        index += 1;
+    }
+    if index < 2 {
+        return Err(TagError::MalformedFile("missing ogg headers".to_string()));
+    }
+    fs::write(path, writer.into_inner())?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use super::{Picture, TagError, TagSet};
+use crate::lyrics::{SyncedLine, SyncedLyrics};
+
+const VENDOR: &str = "deezerapi-rs";
+const FRONT_COVER: u32 = 3;
+
+// Other spellings of a key, replaced along with it so a file never ends up
+// with two different values for the same field
+const ALIASES: [(&str, &str); 3] = [
+    ("TRACKTOTAL", "TOTALTRACKS"),
+    ("DISCTOTAL", "TOTALDISCS"),
+    ("LYRICS", "UNSYNCEDLYRICS"),
+];
+
+pub(super) struct Comments {
+    pub vendor: String,
+    pub comments: Vec<(String, String)>,
+}
+
+impl Comments {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.comments
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn get_all(&self, key: &str) -> Vec<String> {
+        self.comments
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+}
+
+fn read_u32_le(data: &[u8], pos: &mut usize) -> Result<u32, TagError> {
+    let bytes = data
+        .get(*pos..*pos + 4)
+        .ok_or_else(|| TagError::MalformedFile("truncated vorbis comment".to_string()))?;
+    *pos += 4;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_string(data: &[u8], pos: &mut usize) -> Result<String, TagError> {
+    let len = read_u32_le(data, pos)? as usize;
+    let bytes = data
+        .get(*pos..*pos + len)
+        .ok_or_else(|| TagError::MalformedFile("truncated vorbis comment".to_string()))?;
+    *pos += len;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+pub(super) fn decode(data: &[u8]) -> Result<Comments, TagError> {
+    let mut pos = 0;
+    let vendor = read_string(data, &mut pos)?;
+    let count = read_u32_le(data, &mut pos)?;
+    let mut comments = Vec::new();
+    for _ in 0..count {
+        let comment = read_string(data, &mut pos)?;
+        if let Some((key, value)) = comment.split_once('=') {
+            comments.push((key.to_ascii_uppercase(), value.to_string()));
+        }
+    }
+    Ok(Comments { vendor, comments })
+}
+
+pub(super) fn encode(comments: &Comments) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend((comments.vendor.len() as u32).to_le_bytes());
+    data.extend(comments.vendor.as_bytes());
+    data.extend((comments.comments.len() as u32).to_le_bytes());
+    for (key, value) in comments.comments.iter() {
+        let comment = format!("{key}={value}");
+        data.extend((comment.len() as u32).to_le_bytes());
+        data.extend(comment.as_bytes());
+    }
+    data
+}
+
+pub(super) fn merge(existing: Option<Comments>, tags: &TagSet) -> Comments {
+    let mut merged = existing.unwrap_or(Comments {
+        vendor: VENDOR.to_string(),
+        comments: Vec::new(),
+    });
+    // only the keys the TagSet has a value for are replaced, any other comment
+    // already in the file is preserved
+    let comments = comments_from_tags(tags);
+    let replaced: Vec<&str> = comments
+        .iter()
+        .flat_map(|(key, _)| {
+            let aliases = ALIASES
+                .iter()
+                .filter(move |(name, _)| name == key)
+                .map(|(_, alias)| *alias);
+            std::iter::once(key.as_str()).chain(aliases)
+        })
+        .collect();
+    merged.comments.retain(|(key, _)| {
+        !replaced
+            .iter()
+            .any(|replaced| replaced.eq_ignore_ascii_case(key))
+    });
+    merged.comments.extend(comments);
+    merged
+}
+
+fn comments_from_tags(tags: &TagSet) -> Vec<(String, String)> {
+    let mut comments: Vec<(String, String)> = Vec::new();
+    let mut push = |key: &str, value: Option<String>| {
+        if let Some(value) = value {
+            comments.push((key.to_string(), value));
+        }
+    };
+    push("TITLE", tags.title.clone());
+    for artist in tags.artists.iter() {
+        push("ARTIST", Some(artist.clone()));
+    }
+    push("ALBUM", tags.album.clone());
+    push("ALBUMARTIST", tags.album_artist.clone());
+    push("TRACKNUMBER", tags.track_number.map(|n| n.to_string()));
+    push("TRACKTOTAL", tags.track_total.map(|n| n.to_string()));
+    push("DISCNUMBER", tags.disc_number.map(|n| n.to_string()));
+    push("DISCTOTAL", tags.disc_total.map(|n| n.to_string()));
+    push("ISRC", tags.isrc.clone());
+    push("BARCODE", tags.barcode.clone());
+    push("LABEL", tags.label.clone());
+    push("COPYRIGHT", tags.copyright.clone());
+    for genre in tags.genres.iter() {
+        push("GENRE", Some(genre.clone()));
+    }
+    push("DATE", tags.release_date.clone());
+    push("BPM", tags.bpm.map(|bpm| bpm.round().to_string()));
+    push(
+        "REPLAYGAIN_TRACK_GAIN",
+        tags.gain.map(|gain| format!("{gain:.2} dB")),
+    );
+    push("LENGTH", tags.duration.map(|d| (d * 1000).to_string()));
+    push(
+        "ITUNESADVISORY",
+        tags.explicit.map(|e| if e { "1" } else { "0" }.to_string()),
+    );
+    for composer in tags.contributors_with_role("composer") {
+        push("COMPOSER", Some(composer.to_string()));
+    }
+    for author in tags.contributors_with_role("author") {
+        push("LYRICIST", Some(author.to_string()));
+    }
+    push("LYRICS", tags.lyrics.clone());
+    if !tags.synced_lyrics.is_empty() {
+        push("SYNCEDLYRICS", Some(synced_to_lrc(&tags.synced_lyrics)));
+    }
+    push("LYRICS_COPYRIGHT", tags.lyrics_copyright.clone());
+    comments
+}
+
+fn synced_to_lrc(lines: &[(u32, String)]) -> String {
+    let lyrics = SyncedLyrics {
+        lines: lines
+            .iter()
+            .map(|(ms, text)| SyncedLine {
+                start: *ms as u64,
+                duration: None,
+                text: text.clone(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    lyrics.to_lrc()
+}
+
+fn synced_from_lrc(text: &str) -> Vec<(u32, String)> {
+    SyncedLyrics::from_lrc(text)
+        .lines
+        .into_iter()
+        .map(|line| (line.start as u32, line.text))
+        .collect()
+}
+
+pub(super) fn tags_from_comments(comments: &Comments) -> TagSet {
+    let number = |key: &str| {
+        comments
+            .get(key)
+            .and_then(|v| v.split('/').next()?.trim().parse().ok())
+    };
+    let mut contributors = Vec::new();
+    for composer in comments.get_all("COMPOSER") {
+        contributors.push(("composer".to_string(), composer));
+    }
+    for lyricist in comments.get_all("LYRICIST") {
+        contributors.push(("author".to_string(), lyricist));
+    }
+    TagSet {
+        title: comments.get("TITLE").map(str::to_string),
+        artists: comments.get_all("ARTIST"),
+        album: comments.get("ALBUM").map(str::to_string),
+        album_artist: comments.get("ALBUMARTIST").map(str::to_string),
+        track_number: number("TRACKNUMBER"),
+        track_total: number("TRACKTOTAL").or_else(|| number("TOTALTRACKS")),
+        disc_number: number("DISCNUMBER"),
+        disc_total: number("DISCTOTAL").or_else(|| number("TOTALDISCS")),
+        isrc: comments.get("ISRC").map(str::to_string),
+        barcode: comments.get("BARCODE").map(str::to_string),
+        label: comments.get("LABEL").map(str::to_string),
+        copyright: comments.get("COPYRIGHT").map(str::to_string),
+        genres: comments.get_all("GENRE"),
+        release_date: comments.get("DATE").map(str::to_string),
+        bpm: comments.get("BPM").and_then(|v| v.parse().ok()),
+        gain: comments
+            .get("REPLAYGAIN_TRACK_GAIN")
+            .and_then(|v| v.trim_end_matches("dB").trim().parse().ok()),
+        explicit: comments.get("ITUNESADVISORY").map(|v| v == "1"),
+        contributors,
+        lyrics: comments
+            .get("LYRICS")
+            .or_else(|| comments.get("UNSYNCEDLYRICS"))
+            .map(str::to_string),
+        synced_lyrics: comments
+            .get("SYNCEDLYRICS")
+            .map(synced_from_lrc)
+            .unwrap_or_default(),
+        lyrics_copyright: comments.get("LYRICS_COPYRIGHT").map(str::to_string),
+        ..Default::default()
+    }
+}
+
+// FLAC PICTURE block layout, also used base64 encoded in ogg METADATA_BLOCK_PICTURE
+pub(super) fn encode_picture(picture: &Picture) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(FRONT_COVER.to_be_bytes());
+    data.extend((picture.mime_type.len() as u32).to_be_bytes());
+    data.extend(picture.mime_type.as_bytes());
+    // empty description, then width, height, depth and colors left unspecified
+    data.extend([0u8; 20]);
+    data.extend((picture.data.len() as u32).to_be_bytes());
+    data.extend(&picture.data);
+    data
+}
+
+pub(super) fn decode_picture(data: &[u8]) -> Result<Picture, TagError> {
+    let malformed = || TagError::MalformedFile("truncated picture block".to_string());
+    let read_u32 = |pos: usize| -> Result<u32, TagError> {
+        let bytes = data.get(pos..pos + 4).ok_or_else(malformed)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let mime_len = read_u32(4)? as usize;
+    let mime = data.get(8..8 + mime_len).ok_or_else(malformed)?;
+    let description_len = read_u32(8 + mime_len)? as usize;
+    let data_len_pos = 12 + mime_len + description_len + 16;
+    let data_len = read_u32(data_len_pos)? as usize;
+    let picture = data
+        .get(data_len_pos + 4..data_len_pos + 4 + data_len)
+        .ok_or_else(malformed)?;
+    Ok(Picture {
+        mime_type: String::from_utf8_lossy(mime).into_owned(),
+        data: picture.to_vec(),
+    })
+}
@@ -1,3 +1,4 @@
+use crate::models::ids::{AlbumId, ArtistId, GenreId, Isrc, TrackId, Upc};
 use crate::{Deezer, DeezerError};
 use std::fmt::Debug;
 
@@ -21,7 +22,7 @@ async fn refresh_token() {
 
 #[tokio::test]
 async fn test_gw_track() {
-    let track_ids = [1141668, 64555665, 88765665];
+    let track_ids = [1141668, 64555665, 88765665].map(TrackId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.gw_track(track_ids[0]).await,
@@ -34,7 +35,7 @@ async fn test_gw_track() {
 
 #[tokio::test]
 async fn test_gw_song() {
-    let song_ids = [354344, 3545446, 4363245];
+    let song_ids = [354344, 3545446, 4363245].map(TrackId);
     let deezer = Deezer::new();
     let songs = vec![
         deezer.gw_song(song_ids[0]).await,
@@ -47,7 +48,7 @@ async fn test_gw_song() {
 
 #[tokio::test]
 async fn test_gw_songs() {
-    let song_ids = vec![354344, 3545446, 4363245];
+    let song_ids = [354344, 3545446, 4363245].map(TrackId).to_vec();
     let deezer = Deezer::new();
     let songs = deezer.gw_songs(&song_ids).await;
     if let Err(ref error) = songs {
@@ -58,7 +59,7 @@ async fn test_gw_songs() {
 
 #[tokio::test]
 async fn test_gw_songs_from_album() {
-    let album_ids = [122366, 4535, 6566665];
+    let album_ids = [122366, 4535, 6566665].map(AlbumId);
     let deezer = Deezer::new();
     let album_songs = vec![
         deezer.gw_songs_from_album(album_ids[0]).await,
@@ -71,7 +72,7 @@ async fn test_gw_songs_from_album() {
 
 #[tokio::test]
 async fn test_gw_album() {
-    let album_ids = [122366, 4535, 6566665];
+    let album_ids = [122366, 4535, 6566665].map(AlbumId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.gw_album(album_ids[0]).await,
@@ -84,7 +85,7 @@ async fn test_gw_album() {
 
 #[tokio::test]
 async fn test_gw_lyrics() {
-    let song_ids = [1141668, 3443212, 87766453];
+    let song_ids = [1141668, 3443212, 87766453].map(TrackId);
     let deezer = Deezer::new();
     let lyrics = vec![
         deezer.gw_lyrics(song_ids[0]).await,
@@ -97,7 +98,7 @@ async fn test_gw_lyrics() {
 
 #[tokio::test]
 async fn test_track() {
-    let track_ids = [1141668, 64555665, 88765665];
+    let track_ids = [1141668, 64555665, 88765665].map(TrackId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.track(track_ids[0]).await,
@@ -110,12 +111,13 @@ async fn test_track() {
 
 #[tokio::test]
 async fn test_track_from_isrc() {
-    let isrcs = ["TCAFP2196109", "GBUM71507634", "GBAYE0200771"];
+    let isrcs =
+        ["TCAFP2196109", "GBUM71507634", "GBAYE0200771"].map(|isrc| Isrc::new(isrc).unwrap());
     let deezer = Deezer::new();
     let tracks = vec![
-        deezer.track_from_isrc(isrcs[0]).await,
-        deezer.track_from_isrc(isrcs[1]).await,
-        deezer.track_from_isrc(isrcs[2]).await,
+        deezer.track_from_isrc(&isrcs[0]).await,
+        deezer.track_from_isrc(&isrcs[1]).await,
+        deezer.track_from_isrc(&isrcs[2]).await,
     ];
     print_errors_for_items(&isrcs, &tracks);
     assert_eq!(tracks.iter().all(Result::is_ok), true);
@@ -123,7 +125,7 @@ async fn test_track_from_isrc() {
 
 #[tokio::test]
 async fn test_album() {
-    let album_ids = [122366, 6545543, 6566665];
+    let album_ids = [122366, 6545543, 6566665].map(AlbumId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.album(album_ids[0]).await,
@@ -134,14 +136,53 @@ async fn test_album() {
     assert_eq!(albums.iter().all(Result::is_ok), true);
 }
 
+#[tokio::test]
+async fn test_tracks() {
+    let track_ids = [1141668, 64555665, 88765665].map(TrackId);
+    let deezer = Deezer::new().with_concurrency(2);
+    let tracks = deezer.tracks(&track_ids).await;
+    print_errors_for_items(&track_ids, &tracks);
+    assert!(tracks.iter().all(Result::is_ok));
+    let ids: Vec<TrackId> = tracks.into_iter().flatten().map(|t| t.id).collect();
+    assert_eq!(ids, track_ids);
+}
+
+#[tokio::test]
+async fn test_tracks_from_isrcs() {
+    let isrcs =
+        ["TCAFP2196109", "GBUM71507634", "GBAYE0200771"].map(|isrc| Isrc::new(isrc).unwrap());
+    let deezer = Deezer::new();
+    let tracks = deezer.tracks_from_isrcs(&isrcs).await;
+    print_errors_for_items(&isrcs, &tracks);
+    assert!(tracks.iter().all(Result::is_ok));
+}
+
+#[tokio::test]
+async fn test_albums() {
+    let album_ids = [122366, 6545543, 6566665].map(AlbumId);
+    let deezer = Deezer::new();
+    let albums = deezer.albums(&album_ids).await;
+    print_errors_for_items(&album_ids, &albums);
+    assert!(albums.iter().all(Result::is_ok));
+}
+
+#[tokio::test]
+async fn test_artists() {
+    let artist_ids = [27, 13, 1424821].map(ArtistId);
+    let deezer = Deezer::new();
+    let artists = deezer.artists(&artist_ids).await;
+    print_errors_for_items(&artist_ids, &artists);
+    assert!(artists.iter().all(Result::is_ok));
+}
+
 #[tokio::test]
 async fn test_album_from_upc() {
-    let upcs = [3700368446423, 634479384776, 707541948593];
+    let upcs = ["3700368446423", "634479384776", "707541948593"].map(|upc| Upc::new(upc).unwrap());
     let deezer = Deezer::new();
     let albums = vec![
-        deezer.album_from_upc(upcs[0]).await,
-        deezer.album_from_upc(upcs[1]).await,
-        deezer.album_from_upc(upcs[2]).await,
+        deezer.album_from_upc(&upcs[0]).await,
+        deezer.album_from_upc(&upcs[1]).await,
+        deezer.album_from_upc(&upcs[2]).await,
     ];
     print_errors_for_items(&upcs, &albums);
     assert_eq!(albums.iter().all(Result::is_ok), true);
@@ -160,7 +201,7 @@ async fn test_editorial() {
 #[tokio::test]
 async fn test_editorial_from_genre() {
     let deezer = Deezer::new();
-    let editorial = deezer.editorial_from_genre(113).await;
+    let editorial = deezer.editorial_from_genre(GenreId(113)).await;
     if let Err(ref error) = editorial {
         println!("Error {:?} ", error);
     }
@@ -179,7 +220,7 @@ async fn test_genres() {
 
 #[tokio::test]
 async fn test_genre() {
-    let genre_ids = [0, 113, 152];
+    let genre_ids = [0, 113, 152].map(GenreId);
     let deezer = Deezer::new();
     let genres = vec![
         deezer.genre(genre_ids[0]).await,
@@ -192,7 +233,7 @@ async fn test_genre() {
 
 #[tokio::test]
 async fn test_genre_artists() {
-    let genre_ids = [0, 113, 152];
+    let genre_ids = [0, 113, 152].map(GenreId);
     let deezer = Deezer::new();
     let artists = vec![
         deezer.genre_artists(genre_ids[0]).await,
@@ -205,7 +246,7 @@ async fn test_genre_artists() {
 
 #[tokio::test]
 async fn test_genre_radio() {
-    let genre_ids = [0, 113, 152];
+    let genre_ids = [0, 113, 152].map(GenreId);
     let deezer = Deezer::new();
     let radios = vec![
         deezer.genre_radios(genre_ids[0]).await,
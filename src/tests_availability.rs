@@ -0,0 +1,115 @@
+use crate::availability::{Availability, Country, CountryError, CountryFilter, FilterMode, Tier};
+use crate::models::gw;
+use crate::models::ids::{AlbumId, TrackId};
+use crate::transport::MockTransport;
+use crate::{blocking, Deezer};
+use serde_json::{json, Value};
+
+fn gw_track(id: u64, stream_ads: &[&str], stream_sub_only: &[&str]) -> Value {
+    json!({
+        "SNG_ID": id.to_string(),
+        "SNG_TITLE": "One More Time",
+        "ART_ID": "27",
+        "PROVIDER_ID": "3",
+        "ART_NAME": "Daft Punk",
+        "ARTISTS": [],
+        "ALB_ID": "302127",
+        "ALB_TITLE": "Discovery",
+        "TYPE": 0,
+        "DURATION": "320",
+        "ALB_PICTURE": "",
+        "RANK_SNG": "0",
+        "FILESIZE": "0",
+        "MEDIA_VERSION": "1",
+        "DISK_NUMBER": "1",
+        "TRACK_NUMBER": "1",
+        "TRACK_TOKEN": "",
+        "TRACK_TOKEN_EXPIRE": 0,
+        "MEDIA": [],
+        "EXPLICIT_LYRICS": "0",
+        "RIGHTS": {},
+        "ISRC": "GBDUW0000053",
+        "LYRICS_ID": 0,
+        "EXPLICIT_TRACK_CONTENT": {"EXPLICIT_LYRICS_STATUS": 0, "EXPLICIT_COVER_STATUS": 0},
+        "PHYSICAL_RELEASE_DATE": "2001-03-07",
+        "STATUS": 3,
+        "USER_ID": 0,
+        "AVAILABLE_COUNTRIES": {"STREAM_ADS": stream_ads, "STREAM_SUB_ONLY": stream_sub_only},
+        "__TYPE__": "song"
+    })
+}
+
+fn album_body() -> String {
+    json!({
+        "results": {
+            "data": [
+                gw_track(3135553, &["FR", "BE"], &[]),
+                gw_track(3135554, &["BE"], &["FR"]),
+                gw_track(3135555, &[], &[]),
+            ],
+            "count": 3,
+            "total": 3,
+            "filtered_count": 0
+        }
+    })
+    .to_string()
+}
+
+fn album_transport() -> MockTransport {
+    MockTransport::new()
+        .with_gw("deezer.getUserData", "{}")
+        .with_gw("song.getListByAlbum", album_body())
+}
+
+#[test]
+fn test_country() {
+    let france = Country::new("fr").unwrap();
+    assert_eq!(france.as_str(), "FR");
+    assert_eq!("FR".parse::<Country>(), Ok(france));
+    assert_eq!(france.to_string(), "FR");
+    assert_eq!(
+        Country::new("FRA"),
+        Err(CountryError::InvalidCode("FRA".to_string()))
+    );
+    assert!(Country::new("1A").is_err());
+}
+
+#[test]
+fn test_is_available_in() {
+    let france = Country::new("FR").unwrap();
+    let track: gw::Track = serde_json::from_value(gw_track(1, &["BE"], &["FR"])).unwrap();
+    assert!(!track.is_available_in(france, Tier::Free));
+    assert!(track.is_available_in(france, Tier::Premium));
+    let germany = Country::new("DE").unwrap();
+    assert!(!track.is_available_in(germany, Tier::Premium));
+}
+
+#[tokio::test]
+async fn test_country_filter() {
+    let france = Country::new("FR").unwrap();
+    let deezer = Deezer::new().with_transport(album_transport());
+    let album = deezer.gw_songs_from_album(AlbumId(302127)).await.unwrap();
+    assert!(album.unavailable_ids.is_empty());
+    assert!(album.is_available_in(france, Tier::Free));
+    assert!(!album.is_available_in(Country::new("DE").unwrap(), Tier::Premium));
+
+    let filter = CountryFilter::new(france, Tier::Free, FilterMode::Drop);
+    let deezer = deezer.with_country_filter(filter);
+    let album = deezer.gw_songs_from_album(AlbumId(302127)).await.unwrap();
+    let ids: Vec<TrackId> = album.songs.iter().flatten().map(|s| s.sng_id).collect();
+    assert_eq!(ids, [TrackId(3135553)]);
+    assert_eq!(album.unavailable_ids, [TrackId(3135554), TrackId(3135555)]);
+    assert_eq!(album.count, 3);
+}
+
+#[test]
+fn test_country_filter_blocking() {
+    let france = Country::new("FR").unwrap();
+    let filter = CountryFilter::new(france, Tier::Premium, FilterMode::Flag);
+    let deezer = blocking::Deezer::new()
+        .with_transport(album_transport())
+        .with_country_filter(filter);
+    let album = deezer.gw_songs_from_album(AlbumId(302127)).unwrap();
+    assert_eq!(album.songs.unwrap().len(), 3);
+    assert_eq!(album.unavailable_ids, [TrackId(3135555)]);
+}
@@ -1,4 +1,5 @@
 use crate::blocking::{Deezer, DeezerError};
+use crate::models::ids::{AlbumId, ArtistId, GenreId, Isrc, TrackId, Upc};
 use std::fmt::Debug;
 
 pub fn print_errors_for_items<I: Debug, T: Debug>(
@@ -21,7 +22,7 @@ fn refresh_token() {
 
 #[test]
 fn test_gw_track() {
-    let track_ids = [1141668, 64555665, 88765665];
+    let track_ids = [1141668, 64555665, 88765665].map(TrackId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.gw_track(track_ids[0]),
@@ -34,7 +35,7 @@ fn test_gw_track() {
 
 #[test]
 fn test_gw_song() {
-    let song_ids = [354344, 3545446, 4363245];
+    let song_ids = [354344, 3545446, 4363245].map(TrackId);
     let deezer = Deezer::new();
     let songs = vec![
         deezer.gw_song(song_ids[0]),
@@ -47,7 +48,7 @@ fn test_gw_song() {
 
 #[test]
 fn test_gw_songs() {
-    let song_ids = vec![354344, 3545446, 4363245];
+    let song_ids = [354344, 3545446, 4363245].map(TrackId).to_vec();
     let deezer = Deezer::new();
     let songs = deezer.gw_songs(&song_ids);
     if let Err(ref error) = songs {
@@ -58,7 +59,7 @@ fn test_gw_songs() {
 
 #[test]
 fn test_gw_songs_from_album() {
-    let album_ids = [122366, 4535, 6566665];
+    let album_ids = [122366, 4535, 6566665].map(AlbumId);
     let deezer = Deezer::new();
     let album_songs = vec![
         deezer.gw_songs_from_album(album_ids[0]),
@@ -71,7 +72,7 @@ fn test_gw_songs_from_album() {
 
 #[test]
 fn test_gw_album() {
-    let album_ids = [122366, 4535, 6566665];
+    let album_ids = [122366, 4535, 6566665].map(AlbumId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.gw_album(album_ids[0]),
@@ -84,7 +85,7 @@ fn test_gw_album() {
 
 #[test]
 fn test_gw_lyrics() {
-    let song_ids = [1141668, 3443212, 87766453];
+    let song_ids = [1141668, 3443212, 87766453].map(TrackId);
     let deezer = Deezer::new();
     let lyrics = vec![
         deezer.gw_lyrics(song_ids[0]),
@@ -97,7 +98,7 @@ fn test_gw_lyrics() {
 
 #[test]
 fn test_track() {
-    let track_ids = [1141668, 64555665, 88765665];
+    let track_ids = [1141668, 64555665, 88765665].map(TrackId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.track(track_ids[0]),
@@ -110,12 +111,13 @@ fn test_track() {
 
 #[test]
 fn test_track_from_isrc() {
-    let isrcs = ["TCAFP2196109", "GBUM71507634", "GBAYE0200771"];
+    let isrcs =
+        ["TCAFP2196109", "GBUM71507634", "GBAYE0200771"].map(|isrc| Isrc::new(isrc).unwrap());
     let deezer = Deezer::new();
     let tracks = vec![
-        deezer.track_from_isrc(isrcs[0]),
-        deezer.track_from_isrc(isrcs[1]),
-        deezer.track_from_isrc(isrcs[2]),
+        deezer.track_from_isrc(&isrcs[0]),
+        deezer.track_from_isrc(&isrcs[1]),
+        deezer.track_from_isrc(&isrcs[2]),
     ];
     print_errors_for_items(&isrcs, &tracks);
     assert_eq!(tracks.iter().all(Result::is_ok), true);
@@ -123,7 +125,7 @@ fn test_track_from_isrc() {
 
 #[test]
 fn test_album() {
-    let album_ids = [122366, 6545543, 6566665];
+    let album_ids = [122366, 6545543, 6566665].map(AlbumId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.album(album_ids[0]),
@@ -134,14 +136,53 @@ fn test_album() {
     assert_eq!(albums.iter().all(Result::is_ok), true);
 }
 
+#[test]
+fn test_tracks() {
+    let track_ids = [1141668, 64555665, 88765665].map(TrackId);
+    let deezer = Deezer::new().with_concurrency(2);
+    let tracks = deezer.tracks(&track_ids);
+    print_errors_for_items(&track_ids, &tracks);
+    assert!(tracks.iter().all(Result::is_ok));
+    let ids: Vec<TrackId> = tracks.into_iter().flatten().map(|t| t.id).collect();
+    assert_eq!(ids, track_ids);
+}
+
+#[test]
+fn test_tracks_from_isrcs() {
+    let isrcs =
+        ["TCAFP2196109", "GBUM71507634", "GBAYE0200771"].map(|isrc| Isrc::new(isrc).unwrap());
+    let deezer = Deezer::new();
+    let tracks = deezer.tracks_from_isrcs(&isrcs);
+    print_errors_for_items(&isrcs, &tracks);
+    assert!(tracks.iter().all(Result::is_ok));
+}
+
+#[test]
+fn test_albums() {
+    let album_ids = [122366, 6545543, 6566665].map(AlbumId);
+    let deezer = Deezer::new();
+    let albums = deezer.albums(&album_ids);
+    print_errors_for_items(&album_ids, &albums);
+    assert!(albums.iter().all(Result::is_ok));
+}
+
+#[test]
+fn test_artists() {
+    let artist_ids = [27, 13, 1424821].map(ArtistId);
+    let deezer = Deezer::new();
+    let artists = deezer.artists(&artist_ids);
+    print_errors_for_items(&artist_ids, &artists);
+    assert!(artists.iter().all(Result::is_ok));
+}
+
 #[test]
 fn test_album_from_upc() {
-    let upcs = [3700368446423, 634479384776, 707541948593];
+    let upcs = ["3700368446423", "634479384776", "707541948593"].map(|upc| Upc::new(upc).unwrap());
     let deezer = Deezer::new();
     let albums = vec![
-        deezer.album_from_upc(upcs[0]),
-        deezer.album_from_upc(upcs[1]),
-        deezer.album_from_upc(upcs[2]),
+        deezer.album_from_upc(&upcs[0]),
+        deezer.album_from_upc(&upcs[1]),
+        deezer.album_from_upc(&upcs[2]),
     ];
     print_errors_for_items(&upcs, &albums);
     assert_eq!(albums.iter().all(Result::is_ok), true);
@@ -160,7 +201,7 @@ fn test_editorial() {
 #[test]
 fn test_editorial_from_genre() {
     let deezer = Deezer::new();
-    let editorial = deezer.editorial_from_genre(113);
+    let editorial = deezer.editorial_from_genre(GenreId(113));
     if let Err(ref error) = editorial {
         println!("Error {:?} ", error);
     }
@@ -179,7 +220,7 @@ fn test_genres() {
 
 #[test]
 fn test_genre() {
-    let genre_ids = [0, 113, 152];
+    let genre_ids = [0, 113, 152].map(GenreId);
     let deezer = Deezer::new();
     let genres = vec![
         deezer.genre(genre_ids[0]),
@@ -192,7 +233,7 @@ fn test_genre() {
 
 #[test]
 fn test_genre_artists() {
-    let genre_ids = [0, 113, 152];
+    let genre_ids = [0, 113, 152].map(GenreId);
     let deezer = Deezer::new();
     let artists = vec![
         deezer.genre_artists(genre_ids[0]),
@@ -205,7 +246,7 @@ fn test_genre_artists() {
 
 #[test]
 fn test_genre_radio() {
-    let genre_ids = [0, 113, 152];
+    let genre_ids = [0, 113, 152].map(GenreId);
     let deezer = Deezer::new();
     let radios = vec![
         deezer.genre_radios(genre_ids[0]),
@@ -373,3 +414,160 @@ fn test_search_track() {
     print_errors_for_items(&queries, &searches);
     assert_eq!(searches.iter().all(Result::is_ok), true);
 }
+
+// Each method of the blocking client is called with the argument types of
+// the async one, so a different signature or return type fails to compile
+macro_rules! same_methods {
+    ($($(#[$attr:meta])* $kind:ident $method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            $(#[$attr])*
+            #[allow(dead_code)]
+            async fn $method(
+                client: crate::Deezer,
+                blocking: Deezer,
+                $($arg: $ty),*
+            ) {
+                same_methods!(@ $kind client blocking $method $($arg)*);
+            }
+        )*
+
+        // every method listed, whatever the features
+        pub(super) const METHODS: &[&str] = &[$(stringify!($method)),*];
+    };
+    (@ awaited $client:ident $blocking:ident $method:ident $($arg:ident)*) => {
+        let _ = [
+            $client.$method($(Clone::clone(&$arg)),*).await,
+            $blocking.$method($($arg),*),
+        ];
+    };
+    (@ called $client:ident $blocking:ident $method:ident $($arg:ident)*) => {
+        let _ = [
+            $client.$method($(Clone::clone(&$arg)),*),
+            $blocking.$method($($arg),*),
+        ];
+    };
+    // builders return their own client
+    (@ built $client:ident $blocking:ident $method:ident $($arg:ident)*) => {
+        let _: crate::Deezer = $client.$method($(Clone::clone(&$arg)),*);
+        let _: Deezer = $blocking.$method($($arg),*);
+    };
+}
+
+mod same_methods {
+    use super::Deezer;
+    use crate::availability::CountryFilter;
+    use crate::cache::CacheConfig;
+    #[cfg(feature = "tagging")]
+    use crate::library::LocalTrack;
+    use crate::middleware::Middleware;
+    use crate::models::api;
+    use crate::models::ids::{AlbumId, ArtistId, GenreId, Isrc, PlaylistId, TrackId, Upc};
+    use crate::transport::MockTransport;
+    #[cfg(feature = "tagging")]
+    use std::path::Path;
+    use std::sync::Arc;
+
+    same_methods! {
+        built with_transport(transport: Arc<MockTransport>);
+        built with_middleware(middleware: Arc<dyn Middleware>);
+        built with_concurrency(concurrency: usize);
+        built with_cache(config: CacheConfig);
+        built with_country_filter(filter: CountryFilter);
+        built with_language(language: &str);
+        built in_language(language: &str);
+        called cache_stats();
+        called in_flight_requests();
+        called language();
+        called metrics();
+        called reset_metrics();
+        called clear_cache();
+        called invalidate_cache_endpoint(endpoint: &str);
+        called invalidate_cache_path(path: &str);
+        awaited refresh_token();
+        awaited gw_track(song_id: TrackId);
+        awaited gw_song(song_id: TrackId);
+        awaited gw_songs(song_ids: &[TrackId]);
+        awaited gw_songs_from_album(album_id: AlbumId);
+        awaited gw_album(album_id: AlbumId);
+        awaited gw_lyrics(song_id: TrackId);
+        awaited track(song_id: TrackId);
+        awaited track_full(song_id: TrackId);
+        awaited track_from_isrc(isrc: &Isrc);
+        awaited tracks(song_ids: &[TrackId]);
+        awaited tracks_from_isrcs(isrcs: &[Isrc]);
+        awaited album(album_id: AlbumId);
+        awaited albums(album_ids: &[AlbumId]);
+        awaited album_from_upc(upc: &Upc);
+        awaited album_tracks(album_id: AlbumId);
+        awaited artist(artist_id: ArtistId);
+        awaited artists(artist_ids: &[ArtistId]);
+        awaited artist_albums(artist_id: ArtistId);
+        awaited artist_top_tracks(artist_id: ArtistId);
+        awaited artist_related_artists(artist_id: ArtistId);
+        awaited artist_radio(artist_id: ArtistId);
+        awaited artist_playlists(artist_id: ArtistId);
+        awaited editorial();
+        awaited editorial_from_genre(genre_id: GenreId);
+        awaited genres();
+        awaited genre(genre_id: GenreId);
+        awaited genre_artists(genre_id: GenreId);
+        awaited genre_radios(genre_id: GenreId);
+        awaited infos();
+        awaited radios();
+        awaited radio(radio_id: u64);
+        awaited radio_tracks(radio_id: u64);
+        awaited radio_genres();
+        awaited radio_top();
+        awaited radio_lists();
+        awaited playlist(playlist_id: PlaylistId);
+        awaited user(user_id: u64);
+        awaited podcast(podcast_id: u64);
+        awaited episode(episode_id: u64);
+        awaited tracklist(url: &str);
+        awaited hydrate(item: &api::Track);
+        awaited hydrate_all(items: &[api::Album]);
+        awaited resolve_link(url: &str);
+        awaited resolve(url: &str);
+        awaited search(query: &str, strict: bool);
+        awaited search_album(query: &str, strict: bool);
+        awaited search_artist(query: &str, strict: bool);
+        awaited search_playlist(query: &str, strict: bool);
+        awaited search_user(query: &str, strict: bool);
+        awaited search_track(track: &str, artist: &str, album: &str, strict: bool);
+        #[cfg(feature = "tagging")]
+        awaited cover_art(url: &str);
+        #[cfg(feature = "tagging")]
+        awaited match_local_track(local: &LocalTrack);
+        #[cfg(feature = "tagging")]
+        awaited match_library(dir: &Path, report_path: &Path);
+    }
+}
+
+// Public methods declared in the impl Deezer block of a source file
+fn public_methods(source: &str) -> Vec<&str> {
+    let block = &source[source.find("impl Deezer {").unwrap()..];
+    let mut methods: Vec<&str> = block
+        .lines()
+        .filter_map(|line| {
+            let line = line.strip_prefix("    pub ")?;
+            let line = line.strip_prefix("async ").unwrap_or(line);
+            let name = line.strip_prefix("fn ")?;
+            name.split(['(', '<']).next()
+        })
+        .collect();
+    methods.sort_unstable();
+    methods
+}
+
+#[test]
+fn test_same_methods_as_async_client() {
+    let async_methods = public_methods(include_str!("lib.rs"));
+    let blocking_methods = public_methods(include_str!("blocking.rs"));
+    assert!(async_methods.contains(&"track"));
+    assert_eq!(async_methods, blocking_methods);
+    // and same_methods! checks the signature of each of them but new
+    let mut checked = same_methods::METHODS.to_vec();
+    checked.push("new");
+    checked.sort_unstable();
+    assert_eq!(checked, async_methods);
+}
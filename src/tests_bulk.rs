@@ -0,0 +1,150 @@
+use crate::cache::{gw_key, CacheConfig, CacheStorage, CachedBody, FileStorage};
+use crate::metrics::QUOTA_WINDOW;
+use crate::models::ids::{ArtistId, Isrc, TrackId};
+use crate::transport::MockTransport;
+use crate::{blocking, Deezer, DeezerError};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+// Offline cache holding only artist 27, every other id fails without touching the network
+fn offline_config(name: &str) -> CacheConfig {
+    let dir: PathBuf = std::env::temp_dir()
+        .join(format!("deezerapi-rs-{}", std::process::id()))
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    let storage = FileStorage::new(dir).unwrap();
+    let artist = json!({
+        "id": 27,
+        "name": "Daft Punk",
+        "tracklist": "https://api.deezer.com/artist/27/top?limit=50",
+        "type": "artist"
+    });
+    let body = CachedBody {
+        body: artist.to_string(),
+        fetched_at: SystemTime::now(),
+    };
+    storage.put("api:artist/27", &body).unwrap();
+    CacheConfig::default().with_storage(storage).offline(true)
+}
+
+fn not_cached_key<T>(result: &Result<T, DeezerError>) -> Option<&str> {
+    match result {
+        Err(DeezerError::NotCached(key)) => Some(key.as_str()),
+        _ => None,
+    }
+}
+
+fn not_cached_key_blocking<T>(result: &Result<T, blocking::DeezerError>) -> Option<&str> {
+    match result {
+        Err(blocking::DeezerError::NotCached(key)) => Some(key.as_str()),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn test_bulk_results_in_input_order() {
+    let deezer = Deezer::new()
+        .with_cache(offline_config("bulk-async"))
+        .with_concurrency(2);
+    let artist_ids = [13, 27, 1424821, 4050205, 27].map(ArtistId);
+    let artists = deezer.artists(&artist_ids).await;
+    assert_eq!(artists.len(), artist_ids.len());
+    assert_eq!(not_cached_key(&artists[0]), Some("api:artist/13"));
+    assert_eq!(artists[1].as_ref().unwrap().name, "Daft Punk");
+    assert_eq!(not_cached_key(&artists[2]), Some("api:artist/1424821"));
+    assert_eq!(not_cached_key(&artists[3]), Some("api:artist/4050205"));
+    assert_eq!(artists[4].as_ref().unwrap().id, ArtistId(27));
+    assert!(deezer.tracks(&[]).await.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_bulk_waits_for_the_quota() {
+    let artist_ids: Vec<ArtistId> = (1..=60).map(ArtistId).collect();
+    let transport = artist_ids
+        .iter()
+        .fold(MockTransport::new(), |transport, id| {
+            let tracklist = format!("https://api.deezer.com/artist/{}/top?limit=50", id.0);
+            let artist =
+                json!({"id": id.0, "name": "Daft Punk", "tracklist": tracklist, "type": "artist"});
+            transport.with_api(&format!("artist/{}", id.0), artist.to_string())
+        });
+    let deezer = Deezer::new().with_transport(transport).with_concurrency(20);
+    let started = tokio::time::Instant::now();
+    let artists = deezer.artists(&artist_ids).await;
+    assert!(artists.iter().all(Result::is_ok));
+    // 50 requests go out at once, the last 10 wait for the window to slide
+    assert!(started.elapsed() >= QUOTA_WINDOW);
+    assert!(started.elapsed() < QUOTA_WINDOW * 2);
+}
+
+#[test]
+fn test_bulk_results_in_input_order_blocking() {
+    let deezer = blocking::Deezer::new()
+        .with_cache(offline_config("bulk-blocking"))
+        .with_concurrency(3);
+    let isrcs = ["TCAFP2196109", "GBUM71507634"].map(|isrc| Isrc::new(isrc).unwrap());
+    let tracks = deezer.tracks_from_isrcs(&isrcs);
+    let keys: Vec<&str> = tracks.iter().filter_map(not_cached_key_blocking).collect();
+    assert_eq!(
+        keys,
+        ["api:track/isrc:TCAFP2196109", "api:track/isrc:GBUM71507634"]
+    );
+    let artists = deezer.artists(&[ArtistId(13), ArtistId(27)]);
+    assert!(artists[0].is_err());
+    assert_eq!(artists[1].as_ref().unwrap().name, "Daft Punk");
+}
+
+// Every chunk answers with no songs, each one reporting 10 matches in total
+fn seed_song_chunks(storage: &FileStorage, ids: &[TrackId], chunk_size: usize) {
+    for chunk in ids.chunks(chunk_size) {
+        let body = json!({"sng_ids": chunk}).to_string();
+        let key = gw_key("song.getListData", [("body", body.as_str())]);
+        let results = json!({
+            "results": {"data": [], "count": 0, "total": 10, "filtered_count": 1}
+        });
+        let body = CachedBody {
+            body: results.to_string(),
+            fetched_at: SystemTime::now(),
+        };
+        storage.put(&key, &body).unwrap();
+    }
+}
+
+fn song_chunks_config(name: &str, ids: &[TrackId]) -> CacheConfig {
+    let dir = std::env::temp_dir()
+        .join(format!("deezerapi-rs-{}", std::process::id()))
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    let storage = FileStorage::new(dir).unwrap();
+    seed_song_chunks(&storage, ids, 200);
+    CacheConfig::default().with_storage(storage).offline(true)
+}
+
+#[tokio::test]
+async fn test_gw_songs_chunks() {
+    let ids: Vec<TrackId> = (1..=450).map(TrackId).collect();
+    let deezer = Deezer::new().with_cache(song_chunks_config("gw-songs-async", &ids));
+    let songs = deezer.gw_songs(&ids).await.unwrap();
+    assert_eq!((songs.count, songs.total, songs.filtered_count), (0, 30, 3));
+    assert_eq!(songs.songs.unwrap().len(), 0);
+    assert_eq!(songs.missing_ids, ids);
+}
+
+#[test]
+fn test_gw_songs_chunks_blocking() {
+    let ids: Vec<TrackId> = (1..=200).map(TrackId).collect();
+    let config = song_chunks_config("gw-songs-blocking", &ids);
+    let deezer = blocking::Deezer::new().with_cache(config);
+    let songs = deezer.gw_songs(&ids).unwrap();
+    assert_eq!(songs.missing_ids.len(), 200);
+    // the second chunk was never cached, its ids are missing and the first one is kept
+    let ids: Vec<TrackId> = (1..=250).map(TrackId).collect();
+    let songs = deezer.gw_songs(&ids).unwrap();
+    assert_eq!((songs.total, songs.filtered_count), (10, 1));
+    assert_eq!(songs.missing_ids, ids);
+    // without a chunk to keep, the error is returned
+    let ids: Vec<TrackId> = (201..=250).map(TrackId).collect();
+    let err = deezer.gw_songs(&ids).unwrap_err();
+    assert!(matches!(err, blocking::DeezerError::NotCached(_)));
+}
@@ -0,0 +1,213 @@
+use crate::cache::{
+    api_endpoint, api_key, gw_key, CacheConfig, CacheStorage, CachedBody, FileStorage,
+    ResponseCache,
+};
+use crate::models::ids::TrackId;
+use crate::tests_domain::main_track;
+use crate::transport::MockTransport;
+use crate::{Deezer, DeezerError};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("deezerapi-rs-{}", std::process::id()))
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn put(cache: &ResponseCache, path: &str, value: Value) {
+    cache.insert(api_key(path), api_endpoint(path), &value.to_string(), value);
+}
+
+fn get(cache: &ResponseCache, path: &str) -> Option<Value> {
+    cache.get(&api_key(path), api_endpoint(path))
+}
+
+#[test]
+fn test_cache_keys() {
+    assert_eq!(api_endpoint("track/3135556"), "track");
+    assert_eq!(api_endpoint("search?q=daft punk&strict=off"), "search");
+    assert_eq!(
+        api_endpoint("https://api.deezer.com/artist/27/top?index=100"),
+        "artist"
+    );
+    assert_eq!(
+        api_key("https://api.deezer.com/track/3135556"),
+        api_key("track/3135556")
+    );
+    assert_eq!(
+        gw_key("song.getData", [("sng_id", "3135556"), ("lang", "en")]),
+        gw_key("song.getData", [("lang", "en"), ("sng_id", "3135556")])
+    );
+}
+
+#[test]
+fn test_cache_hits_and_lru_eviction() {
+    let cache = ResponseCache::new(CacheConfig::new(2, Duration::from_secs(60)));
+    put(&cache, "track/1", json!({"id": 1}));
+    put(&cache, "track/2", json!({"id": 2}));
+    assert_eq!(get(&cache, "track/1"), Some(json!({"id": 1})));
+    // track/2 is now the least recently used
+    put(&cache, "track/3", json!({"id": 3}));
+    assert_eq!(get(&cache, "track/2"), None);
+    assert!(get(&cache, "track/1").is_some());
+    assert!(get(&cache, "track/3").is_some());
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses), (3, 1));
+    assert_eq!(
+        (stats.insertions, stats.evictions, stats.entries),
+        (3, 1, 2)
+    );
+    assert_eq!(stats.hit_ratio(), 0.75);
+}
+
+#[test]
+fn test_cache_ttl() {
+    let config = CacheConfig::new(10, Duration::from_secs(60))
+        .with_ttl("search", Duration::ZERO)
+        .with_ttl("chart", Duration::from_millis(1));
+    let cache = ResponseCache::new(config);
+    put(&cache, "search?q=a", json!({}));
+    put(&cache, "chart", json!({}));
+    assert_eq!(cache.stats().entries, 1);
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(get(&cache, "chart"), None);
+    assert_eq!(cache.stats().expirations, 1);
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[test]
+fn test_cache_invalidation() {
+    let cache = ResponseCache::new(CacheConfig::default());
+    put(&cache, "artist/27", json!({}));
+    put(&cache, "artist/27?limit=100", json!({}));
+    put(&cache, "artist/27/top", json!({}));
+    put(&cache, "artist/270", json!({}));
+    let gw = gw_key("song.getData", [("sng_id", "1")]);
+    cache.insert(gw.clone(), "song.getData", "{}", json!({}));
+    cache.invalidate_api_path("artist/27");
+    assert!(get(&cache, "artist/27").is_none());
+    assert!(get(&cache, "artist/27?limit=100").is_none());
+    assert!(get(&cache, "artist/27/top").is_some());
+    assert!(get(&cache, "artist/270").is_some());
+    cache.invalidate_endpoint("artist");
+    assert_eq!(cache.stats().entries, 1);
+    cache.invalidate_endpoint("song.getData");
+    assert!(cache.get(&gw, "song.getData").is_none());
+    put(&cache, "album/302127", json!({}));
+    cache.clear();
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[test]
+fn test_cache_shared_across_clones() {
+    let deezer = Deezer::new();
+    assert!(deezer.cache_stats().is_none());
+    let deezer = deezer.with_cache(CacheConfig::default());
+    let clone = deezer.clone();
+    clone.clear_cache();
+    assert_eq!(deezer.cache_stats(), clone.cache_stats());
+    assert!(deezer.cache_stats().is_some());
+}
+
+#[test]
+fn test_file_storage() {
+    let storage = FileStorage::new(temp_dir("file-storage")).unwrap();
+    let body = CachedBody {
+        body: r#"{"id":3135556}"#.to_string(),
+        fetched_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+    };
+    assert_eq!(storage.get("api:track/3135556").unwrap(), None);
+    storage.put("api:track/3135556", &body).unwrap();
+    assert_eq!(storage.get("api:track/3135556").unwrap(), Some(body));
+    assert_eq!(storage.keys().unwrap(), ["api:track/3135556"]);
+    storage.remove("api:track/3135556").unwrap();
+    storage.remove("api:track/3135556").unwrap();
+    assert!(storage.keys().unwrap().is_empty());
+}
+
+#[test]
+fn test_file_storage_keys() {
+    let dir = temp_dir("file-storage-keys");
+    let storage = FileStorage::new(&dir).unwrap();
+    let body = CachedBody {
+        body: "{}".to_string(),
+        fetched_at: SystemTime::now(),
+    };
+    storage.put("api:track/3135556", &body).unwrap();
+    storage.put("api:track/3135553", &body).unwrap();
+    // a partly written file is left out instead of failing the listing
+    std::fs::write(dir.join("0000000000000000.json"), b"{\"key\":").unwrap();
+    let mut keys = storage.keys().unwrap();
+    keys.sort();
+    assert_eq!(keys, ["api:track/3135553", "api:track/3135556"]);
+
+    let cache = ResponseCache::new(CacheConfig::default().with_storage(storage.clone()));
+    cache.invalidate_api_path("track/3135556");
+    assert_eq!(storage.keys().unwrap(), ["api:track/3135553"]);
+    assert_eq!(cache.stats().storage_errors, 0);
+}
+
+#[tokio::test]
+async fn test_client_file_storage() {
+    let dir = temp_dir("client-storage");
+    let config = || {
+        CacheConfig::new(10, Duration::from_secs(60)).with_storage(FileStorage::new(&dir).unwrap())
+    };
+    let transport = MockTransport::new().with_api("track/3135553", main_track());
+    let deezer = Deezer::new().with_transport(transport).with_cache(config());
+    deezer.track(TrackId(3135553)).await.unwrap();
+
+    // a new client without transport answers from the storage
+    let deezer = Deezer::new()
+        .with_transport(MockTransport::new())
+        .with_cache(config());
+    let track = deezer.track(TrackId(3135553)).await.unwrap();
+    assert_eq!(track.title, "One More Time");
+    assert_eq!(deezer.cache_stats().unwrap().storage_hits, 1);
+}
+
+#[test]
+fn test_cache_storage_survives_restart() {
+    let dir = temp_dir("persistent-cache");
+    let config = || {
+        CacheConfig::new(10, Duration::from_secs(60))
+            .with_storage(FileStorage::new(&dir).unwrap())
+            .with_ttl("chart", Duration::from_millis(1))
+    };
+    let cache = ResponseCache::new(config());
+    put(&cache, "track/3135556", json!({"id": 3135556}));
+    put(&cache, "chart", json!({"tracks": []}));
+    drop(cache);
+
+    let cache = ResponseCache::new(config());
+    assert_eq!(get(&cache, "track/3135556"), Some(json!({"id": 3135556})));
+    assert_eq!(cache.stats().storage_hits, 1);
+    // now in memory
+    assert!(get(&cache, "track/3135556").is_some());
+    assert_eq!(cache.stats().storage_hits, 1);
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(get(&cache, "chart"), None);
+
+    // offline mode serves stale entries
+    let cache = ResponseCache::new(config().offline(true));
+    assert_eq!(get(&cache, "chart"), Some(json!({"tracks": []})));
+
+    cache.invalidate_api_path("track/3135556");
+    let cache = ResponseCache::new(config());
+    assert_eq!(get(&cache, "track/3135556"), None);
+}
+
+#[tokio::test]
+async fn test_cache_offline_never_fetches() {
+    let deezer = Deezer::new().with_cache(CacheConfig::default().offline(true));
+    match deezer.track(TrackId(3135556)).await {
+        Err(DeezerError::NotCached(key)) => assert_eq!(key, "api:track/3135556"),
+        other => panic!("expected a NotCached error, got {other:?}"),
+    }
+    let gw = deezer.gw_song(TrackId(3135556)).await;
+    assert!(matches!(gw, Err(DeezerError::NotCached(_))));
+}
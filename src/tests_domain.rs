@@ -0,0 +1,334 @@
+use crate::models::domain::{Album, Artist, Track};
+use crate::models::fields::Date;
+use crate::models::ids::{AlbumId, ArtistId, Isrc, TrackId};
+use crate::models::{api, gw};
+use crate::transport::MockTransport;
+use crate::{blocking, Deezer};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+pub(crate) fn gw_track(id: &str) -> Value {
+    json!({
+        "SNG_ID": id,
+        "SNG_TITLE": "One More Time",
+        "ART_ID": "27",
+        "PROVIDER_ID": "3",
+        "ART_NAME": "Daft Punk",
+        "ARTISTS": [{
+            "ART_ID": "27",
+            "ROLE_ID": "0",
+            "ARTISTS_SONGS_ORDER": "0",
+            "ART_NAME": "Daft Punk",
+            "ARTIST_IS_DUMMY": false,
+            "ART_PICTURE": "f2bc007e9133c946ac3c3907ddc5d2ea",
+            "RANK": "1000",
+            "LOCALES": [],
+            "__TYPE__": "artist"
+        }],
+        "ALB_ID": "302127",
+        "ALB_TITLE": "Discovery",
+        "TYPE": 0,
+        "DURATION": "320",
+        "ALB_PICTURE": "2e018122cb56986277102d2041a592c8",
+        "RANK_SNG": "",
+        "FILESIZE": "0",
+        "FILESIZE_MP3_128": "3842614",
+        "FILESIZE_FLAC": "",
+        "GAIN": "-12.5",
+        "MEDIA_VERSION": "1",
+        "DISK_NUMBER": "1",
+        "TRACK_NUMBER": "1",
+        "TRACK_TOKEN": "",
+        "TRACK_TOKEN_EXPIRE": 0,
+        "MEDIA": [],
+        "EXPLICIT_LYRICS": "0",
+        "RIGHTS": {},
+        "ISRC": "GBDUW0000053",
+        "LYRICS_ID": 0,
+        "SNG_CONTRIBUTORS": {"composer": ["Thomas Bangalter", "Guy-Manuel de Homem-Christo"]},
+        "EXPLICIT_TRACK_CONTENT": {"EXPLICIT_LYRICS_STATUS": 0, "EXPLICIT_COVER_STATUS": 0},
+        "PHYSICAL_RELEASE_DATE": "2001-03-07",
+        "STATUS": 3,
+        "USER_ID": 0,
+        "__TYPE__": "song"
+    })
+}
+
+fn api_track() -> api::Track {
+    api::Track {
+        id: TrackId(3135553),
+        title: "One More Time".to_string(),
+        duration: 320,
+        rank: 860000,
+        explicit_lyrics: false,
+        md5_image: "2e018122cb56986277102d2041a592c8".to_string(),
+        artist: api::Artist {
+            id: ArtistId(27),
+            name: "Daft Punk".to_string(),
+            picture_xl: Some("https://e-cdns-images.dzcdn.net/artist.jpg".to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_from_gw_track() {
+    let track: gw::Track = serde_json::from_value(gw_track("3135553")).unwrap();
+    let track = Track::from(track);
+    assert_eq!(track.id, TrackId(3135553));
+    assert_eq!(track.duration, Some(320));
+    assert_eq!(track.rank, None);
+    assert_eq!(track.explicit, Some(false));
+    assert_eq!(track.gain, Some(-12.5));
+    assert_eq!(track.release_date.as_deref(), Some("2001-03-07"));
+    assert_eq!(track.artists.len(), 1);
+    assert_eq!(
+        track.artists[0].md5_image.as_deref(),
+        Some("f2bc007e9133c946ac3c3907ddc5d2ea")
+    );
+    let album = track.album.unwrap();
+    assert_eq!(
+        (album.id, album.title.as_str()),
+        (AlbumId(302127), "Discovery")
+    );
+
+    // ids are checked when deserializing, an empty one is the default id
+    assert!(serde_json::from_value::<gw::Track>(gw_track("a27")).is_err());
+    let mut value = gw_track("");
+    value["GENRE_ID"] = json!("");
+    let track: gw::Track = serde_json::from_value(value).unwrap();
+    assert_eq!((track.sng_id, track.genre_id), (TrackId(0), None));
+    // and gw ids are written back as strings
+    let exported = serde_json::to_value(&track).unwrap();
+    assert_eq!(exported["SNG_ID"], "");
+    assert_eq!(exported["GENRE_ID"], "");
+    assert_eq!(exported["ALB_ID"], "302127");
+}
+
+#[test]
+fn test_gw_fields() {
+    let track: gw::Track = serde_json::from_value(gw_track("3135553")).unwrap();
+    assert_eq!(track.duration, Some(Duration::from_secs(320)));
+    assert_eq!((track.disk_number, track.track_number), (Some(1), Some(1)));
+    assert_eq!(track.rank_sng, None);
+    assert_eq!(track.filesize_mp3_128, Some(3842614));
+    assert_eq!(
+        (track.filesize, track.filesize_flac, track.filesize_aac_64),
+        (Some(0), None, None)
+    );
+    assert_eq!(track.explicit_lyrics, Some(false));
+    assert_eq!(
+        track
+            .physical_release_date
+            .map(|d| d.to_string())
+            .as_deref(),
+        Some("2001-03-07")
+    );
+
+    // numbers sent as numbers, empty strings and unknown dates
+    let mut value = gw_track("3135553");
+    value["DURATION"] = json!(320);
+    value["TRACK_NUMBER"] = json!("");
+    value["RANK_SNG"] = json!(860000);
+    value["EXPLICIT_LYRICS"] = json!(true);
+    value["PHYSICAL_RELEASE_DATE"] = json!("0000-00-00");
+    value["DIGITAL_RELEASE_DATE"] = json!("2001-03-12");
+    let track: gw::Track = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(track.duration, Some(Duration::from_secs(320)));
+    assert_eq!((track.track_number, track.rank_sng), (None, Some(860000)));
+    assert_eq!(track.explicit_lyrics, Some(true));
+    assert_eq!(track.physical_release_date, None);
+    assert_eq!(
+        track.digital_release_date,
+        Some(Date {
+            year: 2001,
+            month: 3,
+            day: 12
+        })
+    );
+    assert_eq!(Track::from(track).release_date, None);
+
+    value["DURATION"] = json!("5:20");
+    assert!(serde_json::from_value::<gw::Track>(value).is_err());
+    assert!("2001-13-07".parse::<Date>().is_err());
+    assert!("2001-3-7".parse::<Date>().is_err());
+}
+
+#[test]
+fn test_from_api_track() {
+    let track = Track::from(api_track());
+    assert_eq!(track.rank, Some(860000));
+    assert_eq!(track.isrc, None);
+    // album lists leave the album out
+    assert_eq!(track.album, None);
+    assert_eq!(track.artists[0].name, "Daft Punk");
+}
+
+#[test]
+fn test_merge() {
+    let gw: gw::Track = serde_json::from_value(gw_track("3135553")).unwrap();
+    let gw = Track::from(gw);
+    let merged = Track::from(api_track()).merge(gw);
+    assert_eq!(merged.rank, Some(860000));
+    assert_eq!(merged.isrc, Isrc::new("GBDUW0000053").ok());
+    assert_eq!(merged.track_number, Some(1));
+    assert_eq!(merged.album.as_ref().unwrap().title, "Discovery");
+    assert_eq!(merged.artists.len(), 1);
+    let artist = &merged.artists[0];
+    assert!(artist.picture.is_some() && artist.md5_image.is_some());
+
+    let album = Album {
+        id: AlbumId(302127),
+        label: Some("Parlophone".to_string()),
+        ..Default::default()
+    };
+    let other = Album {
+        id: AlbumId(302127),
+        title: "Discovery".to_string(),
+        label: Some("Virgin".to_string()),
+        artist: Some(Artist {
+            id: ArtistId(27),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let album = album.merge(other);
+    assert_eq!(album.title, "Discovery");
+    assert_eq!(album.label.as_deref(), Some("Parlophone"));
+    assert_eq!(album.artist.map(|a| a.id), Some(ArtistId(27)));
+}
+
+#[test]
+fn test_from_api_album_without_date() {
+    let album: api::Album = serde_json::from_value(json!({
+        "id": 302127,
+        "title": "Discovery",
+        "cover": "",
+        "md5_image": "",
+        "tracklist": "",
+        "release_date": "0000-00-00",
+        "record_type": "",
+        "type": "album"
+    }))
+    .unwrap();
+    let album = Album::from(album);
+    assert_eq!(album.release_date, None);
+    assert_eq!(album.record_type, None);
+    let other = Album {
+        release_date: Some("2001-03-07".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        album.merge(other).release_date.as_deref(),
+        Some("2001-03-07")
+    );
+}
+
+pub(crate) fn main_track() -> String {
+    json!({
+        "id": 3135553,
+        "readable": true,
+        "title": "One More Time",
+        "title_short": "One More Time",
+        "title_version": "",
+        "isrc": "GBDUW0000053",
+        "link": "https://www.deezer.com/track/3135553",
+        "share": "",
+        "duration": 320,
+        "track_position": 1,
+        "disk_number": 1,
+        "rank": 860000,
+        "release_date": "2001-03-07",
+        "explicit_lyrics": false,
+        "explicit_content_lyrics": 0,
+        "explicit_content_cover": 0,
+        "preview": "",
+        "bpm": 122.7,
+        "gain": -12.4,
+        "available_countries": ["FR"],
+        "contributors": [],
+        "md5_image": "2e018122cb56986277102d2041a592c8",
+        "artist": {"id": 27, "name": "Daft Punk", "tracklist": "", "type": "artist"},
+        "album": {
+            "id": 302127,
+            "title": "Discovery",
+            "cover": "",
+            "md5_image": "2e018122cb56986277102d2041a592c8",
+            "tracklist": "",
+            "type": "album"
+        },
+        "type": "track"
+    })
+    .to_string()
+}
+
+pub(crate) fn page_track() -> String {
+    let empty = json!({"data": [], "count": 0, "total": 0});
+    json!({
+        "results": {
+            "DATA": gw_track("3135553"),
+            "ISRC": empty,
+            "RELATED_ALBUMS": empty
+        }
+    })
+    .to_string()
+}
+
+fn lyrics() -> String {
+    json!({
+        "results": {
+            "LYRICS_ID": "2780622",
+            "LYRICS_TEXT": "One more time",
+            "LYRICS_COPYRIGHTS": "",
+            "LYRICS_WRITERS": ""
+        }
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_track_full() {
+    let transport = MockTransport::new()
+        .with_api("track/3135553", main_track())
+        .with_gw("deezer.getUserData", "{}")
+        .with_gw("deezer.pageTrack", page_track())
+        .with_gw("song.getLyrics", lyrics());
+    let deezer = Deezer::new().with_transport(transport);
+    let full = deezer.track_full(TrackId(3135553)).await.unwrap();
+    assert!(full.errors.is_empty());
+    assert_eq!(full.track.bpm, Some(122.7));
+    assert_eq!(full.track.gain, Some(-12.4));
+    assert_eq!(full.file_sizes.mp3_128, Some(3842614));
+    assert_eq!(full.file_sizes.flac, None);
+    assert_eq!(full.contributors["composer"].len(), 2);
+    assert_eq!(full.lyrics.unwrap().lyrics_text, "One more time");
+}
+
+#[tokio::test]
+async fn test_track_full_partial() {
+    let transport = MockTransport::new()
+        .with_gw("deezer.getUserData", "{}")
+        .with_gw("deezer.pageTrack", page_track());
+    let deezer = Deezer::new().with_transport(transport);
+    let full = deezer.track_full(TrackId(3135553)).await.unwrap();
+    let kinds: Vec<_> = full.errors.iter().map(|err| err.kind()).collect();
+    assert_eq!(kinds, ["transport", "transport"]);
+    assert_eq!(full.track.bpm, None);
+    assert_eq!(full.track.gain, Some(-12.5));
+    assert!(full.lyrics.is_none());
+
+    let deezer = Deezer::new().with_transport(MockTransport::new());
+    assert!(deezer.track_full(TrackId(3135553)).await.is_err());
+}
+
+#[test]
+fn test_track_full_blocking() {
+    let transport = MockTransport::new()
+        .with_api("track/3135553", main_track())
+        .with_gw("deezer.getUserData", "{}");
+    let deezer = blocking::Deezer::new().with_transport(transport);
+    let full = deezer.track_full(TrackId(3135553)).unwrap();
+    assert_eq!(full.track.album.unwrap().title, "Discovery");
+    assert_eq!(full.errors.len(), 2);
+}
@@ -0,0 +1,128 @@
+use crate::models::domain::Track;
+use crate::models::export::{to_json, to_value, ExportFormat};
+use crate::models::{api, gw};
+use crate::tests_domain::{gw_track, main_track, page_track};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// Every value of the payload must be exported unchanged, the export may add
+// the fields missing from the payload
+fn assert_exported(exported: &Value, payload: &Value, path: &str) {
+    match (exported, payload) {
+        (Value::Object(exported), Value::Object(payload)) => {
+            for (key, value) in payload {
+                let path = format!("{path}.{key}");
+                let exported = exported.get(key).unwrap_or(&Value::Null);
+                assert_exported(exported, value, &path);
+            }
+        }
+        (Value::Array(exported), Value::Array(payload)) => {
+            assert_eq!(exported.len(), payload.len(), "{path}");
+            for (i, (exported, value)) in exported.iter().zip(payload).enumerate() {
+                assert_exported(exported, value, &format!("{path}[{i}]"));
+            }
+        }
+        _ => assert_eq!(exported, payload, "{path}"),
+    }
+}
+
+// Deserializes the payload, exports it and deserializes the export again, both
+// exports must match and keep every value of the payload
+fn round_trip<T: Serialize + DeserializeOwned>(payload: Value) -> Value {
+    let model: T = serde_json::from_value(payload.clone()).unwrap();
+    let exported = to_value(&model, ExportFormat::Deezer).unwrap();
+    let again: T = serde_json::from_value(exported.clone()).unwrap();
+    assert_eq!(to_value(&again, ExportFormat::Deezer).unwrap(), exported);
+    assert_exported(&exported, &payload, "");
+    exported
+}
+
+fn song() -> Value {
+    let mut song = gw_track("3135553");
+    // songs have RANK instead of RANK_SNG, and no TYPE
+    let fields = song.as_object_mut().unwrap();
+    fields.remove("RANK_SNG");
+    fields.remove("TYPE");
+    song["ARTIST_IS_DUMMY"] = json!(false);
+    song["DIGITAL_RELEASE_DATE"] = json!("0000-00-00");
+    song["FALLBACK"] = json!({"SNG_ID": "3135556"});
+    song["GENRE_ID"] = json!("113");
+    song["HIERARCHICAL_TITLE"] = json!("");
+    song["RANK"] = json!("860000");
+    song["SMARTRADIO"] = json!(0);
+    song
+}
+
+#[test]
+fn test_round_trip() {
+    let track: Value = serde_json::from_str(&main_track()).unwrap();
+    let exported = round_trip::<api::MainTrack>(track.clone());
+    assert_eq!(exported["isrc"], track["isrc"]);
+    assert_eq!(exported["artist"]["type"], "artist");
+
+    let page: Value = serde_json::from_str(&page_track()).unwrap();
+    let exported = round_trip::<gw::TrackData>(page["results"].clone());
+    let data = &exported["DATA"];
+    assert_eq!(data["SNG_ID"], "3135553");
+    // typed gw fields are written back as gw sends them
+    assert_eq!(data["DURATION"], "320");
+    assert_eq!(data["EXPLICIT_LYRICS"], "0");
+    assert_eq!(data["PHYSICAL_RELEASE_DATE"], "2001-03-07");
+    assert_eq!(data["RANK_SNG"], "");
+    assert_eq!(data["__TYPE__"], "song");
+
+    let exported = round_trip::<gw::Song>(song());
+    assert_eq!(exported["FALLBACK"]["SNG_ID"], "3135556");
+    assert_eq!(exported["DIGITAL_RELEASE_DATE"], "0000-00-00");
+
+    let lyrics = json!({
+        "LYRICS_ID": "2780622",
+        "LYRICS_SYNC_JSON": [{"lrc_timestamp": "[00:12.34]", "milliseconds": "12340", "duration": "1710", "line": "One more time"}],
+        "LYRICS_TEXT": "One more time",
+        "LYRICS_COPYRIGHTS": "",
+        "LYRICS_WRITERS": ""
+    });
+    round_trip::<gw::Lyrics>(lyrics);
+}
+
+// Keys of the value and of every nested object
+fn key_count(value: &Value) -> usize {
+    match value {
+        Value::Object(map) => map.len() + map.values().map(key_count).sum::<usize>(),
+        Value::Array(values) => values.iter().map(key_count).sum(),
+        _ => 0,
+    }
+}
+
+#[test]
+fn test_normalized() {
+    let page: Value = serde_json::from_str(&page_track()).unwrap();
+    let track: gw::Track = serde_json::from_value(page["results"]["DATA"].clone()).unwrap();
+    let normalized = to_value(&track, ExportFormat::Normalized).unwrap();
+    assert_eq!(normalized["sng_id"], "3135553");
+    // TYPE and __TYPE__ would both be type
+    assert_eq!(normalized["type"], 0);
+    assert_eq!(normalized["__type__"], "song");
+    let deezer = to_value(&track, ExportFormat::Deezer).unwrap();
+    assert_eq!(key_count(&normalized), key_count(&deezer));
+    assert_eq!(normalized["filesize_mp3_128"], "3842614");
+    assert_eq!(normalized["artists"][0]["art_name"], "Daft Punk");
+    assert_eq!(
+        normalized["explicit_track_content"]["explicit_lyrics_status"],
+        0
+    );
+    assert!(normalized.get("SNG_ID").is_none());
+
+    // keys are sorted, so exports are stable
+    let json = to_json(&track, ExportFormat::Normalized).unwrap();
+    assert!(json.starts_with(r#"{"__type__":"song","alb_id":"302127","#));
+    assert_eq!(
+        json,
+        to_json(&track.clone(), ExportFormat::Normalized).unwrap()
+    );
+
+    let domain = to_value(&Track::from(track), ExportFormat::Deezer).unwrap();
+    assert_eq!(domain["release_date"], "2001-03-07");
+    assert_eq!(domain["album"]["title"], "Discovery");
+}
@@ -0,0 +1,77 @@
+use crate::cache::CacheConfig;
+use crate::hydrate::Hydrate;
+use crate::models::api;
+use crate::models::ids::ArtistId;
+use crate::transport::MockTransport;
+use crate::{blocking, Deezer};
+use serde_json::json;
+use std::sync::Arc;
+
+fn full_artist(id: u64, name: &str) -> String {
+    json!({
+        "id": id,
+        "name": name,
+        "link": format!("https://www.deezer.com/artist/{id}"),
+        "picture_xl": "",
+        "nb_album": 30,
+        "nb_fan": 4000000,
+        "radio": true,
+        "tracklist": format!("https://api.deezer.com/artist/{id}/top?limit=50"),
+        "type": "artist"
+    })
+    .to_string()
+}
+
+fn partial_artist(id: u64) -> api::Artist {
+    api::Artist {
+        id: ArtistId(id),
+        ..Default::default()
+    }
+}
+
+fn artist_transport() -> MockTransport {
+    MockTransport::new()
+        .with_api("artist/27", full_artist(27, "Daft Punk"))
+        .with_api("artist/1", full_artist(1, "Justice"))
+}
+
+#[tokio::test]
+async fn test_hydrate() {
+    let deezer = Deezer::new().with_transport(artist_transport());
+    let artist = partial_artist(27).hydrate(&deezer).await.unwrap();
+    assert_eq!(artist.name, "Daft Punk");
+    assert_eq!(artist.nb_album, Some(30));
+    let contributor = api::Contributor {
+        id: ArtistId(1),
+        ..Default::default()
+    };
+    assert_eq!(deezer.hydrate(&contributor).await.unwrap().name, "Justice");
+}
+
+#[tokio::test]
+async fn test_hydrate_all() {
+    let transport = Arc::new(artist_transport());
+    let deezer = Deezer::new()
+        .with_transport(transport.clone())
+        .with_cache(CacheConfig::default());
+    let artists: Vec<_> = [27, 2, 1, 27].into_iter().map(partial_artist).collect();
+    let hydrated = deezer.hydrate_all(&artists).await;
+    let names: Vec<_> = hydrated
+        .iter()
+        .map(|r| r.as_ref().map(|a| a.name.as_str()).ok())
+        .collect();
+    assert_eq!(
+        names,
+        [Some("Daft Punk"), None, Some("Justice"), Some("Daft Punk")]
+    );
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[test]
+fn test_hydrate_blocking() {
+    let deezer = blocking::Deezer::new().with_transport(artist_transport());
+    let artist = deezer.hydrate(&partial_artist(1)).unwrap();
+    assert_eq!(artist.name, "Justice");
+    let hydrated = deezer.hydrate_all(&[partial_artist(27)]);
+    assert_eq!(hydrated[0].as_ref().unwrap().name, "Daft Punk");
+}
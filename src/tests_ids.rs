@@ -0,0 +1,44 @@
+use crate::models::ids::{GenreId, IdError, Isrc, TrackId, Upc};
+
+#[test]
+fn test_numeric_ids() {
+    assert_eq!(
+        serde_json::from_str::<TrackId>("3135556").unwrap(),
+        TrackId(3135556)
+    );
+    assert_eq!(
+        serde_json::from_str::<TrackId>("\"3135556\"").unwrap(),
+        TrackId(3135556)
+    );
+    assert!(serde_json::from_str::<TrackId>("\"\"").is_err());
+    assert!(serde_json::from_str::<TrackId>("-1").is_err());
+    assert_eq!(serde_json::from_str::<GenreId>("-1").unwrap(), GenreId(-1));
+    assert_eq!("27".parse::<TrackId>(), Ok(TrackId(27)));
+    assert_eq!(
+        "a27".parse::<TrackId>(),
+        Err(IdError::InvalidId("a27".to_string()))
+    );
+    assert_eq!(TrackId(27).to_string(), "27");
+    assert_eq!(serde_json::to_string(&TrackId(27)).unwrap(), "27");
+}
+
+#[test]
+fn test_codes() {
+    assert_eq!(
+        Isrc::new("gb-duw-00-00053").unwrap().as_str(),
+        "GBDUW0000053"
+    );
+    assert_eq!(
+        Isrc::new("GBDUW000005"),
+        Err(IdError::InvalidIsrc("GBDUW000005".to_string()))
+    );
+    assert!(Isrc::new("12DUW0000053").is_err());
+    assert_eq!(
+        Upc::new("0724384960650").unwrap().to_string(),
+        "0724384960650"
+    );
+    assert!(Upc::new("07243849606a").is_err());
+    assert!(Upc::new("1234567").is_err());
+    // responses keep empty codes
+    assert_eq!(serde_json::from_str::<Isrc>("\"\"").unwrap().as_str(), "");
+}
@@ -0,0 +1,69 @@
+use crate::cache::{localized_key, CacheConfig};
+use crate::models::ids::{ArtistId, TrackId};
+use crate::transport::MockTransport;
+use crate::{blocking, Deezer};
+use std::sync::Arc;
+
+const ARTIST: &str = r#"{"id":27,"name":"Daft Punk","tracklist":"","type":"artist"}"#;
+
+#[test]
+fn test_localized_key() {
+    assert_eq!(
+        localized_key("api:genre/0".to_string(), "en"),
+        "api:genre/0"
+    );
+    assert_eq!(
+        localized_key("api:genre/0".to_string(), "fr"),
+        "api:genre/0?lang=fr"
+    );
+    assert_eq!(
+        localized_key("gw:song.getData?sng_id=1".to_string(), "de"),
+        "gw:song.getData?sng_id=1&lang=de"
+    );
+}
+
+#[tokio::test]
+async fn test_language_per_call() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .with_api("artist/27", ARTIST)
+            .with_gw("deezer.getUserData", "{}"),
+    );
+    let deezer = Deezer::new()
+        .with_transport(transport.clone())
+        .with_cache(CacheConfig::default());
+    let french = deezer.in_language("fr");
+    assert_eq!((deezer.language(), french.language()), ("en", "fr"));
+    deezer.artist(ArtistId(27)).await.unwrap();
+    french.artist(ArtistId(27)).await.unwrap();
+    // each language is cached on its own
+    french.artist(ArtistId(27)).await.unwrap();
+    deezer.artist(ArtistId(27)).await.unwrap();
+    assert!(french.gw_song(TrackId(3135556)).await.is_err());
+
+    let requests = transport.requests();
+    let languages: Vec<_> = requests
+        .iter()
+        .map(|r| r.header("Accept-Language").unwrap())
+        .collect();
+    assert_eq!(languages, ["en", "fr", "fr", "fr"]);
+    assert_eq!(requests[2].param("lang").as_deref(), Some("fr"));
+    assert_eq!(requests[3].gw_method().as_deref(), Some("song.getData"));
+    assert_eq!(deezer.cache_stats().unwrap().entries, 2);
+}
+
+#[test]
+fn test_language_blocking() {
+    let transport = Arc::new(MockTransport::new().with_api("artist/27", ARTIST));
+    let deezer = blocking::Deezer::new()
+        .with_transport(transport.clone())
+        .with_language("de");
+    deezer.artist(ArtistId(27)).unwrap();
+    deezer.in_language("es").artist(ArtistId(27)).unwrap();
+    let languages: Vec<_> = transport
+        .requests()
+        .iter()
+        .map(|r| r.header("accept-language").unwrap().to_string())
+        .collect();
+    assert_eq!(languages, ["de", "es"]);
+}
@@ -0,0 +1,264 @@
+use crate::library::{
+    audio_files, score, Candidate, Confidence, FileMatch, LocalTrack, MatchReport, MatchStatus,
+};
+use crate::models;
+use crate::models::ids::TrackId;
+use crate::transport::MockTransport;
+use crate::Deezer;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("deezerapi-rs-{}", std::process::id()))
+        .join(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn local_track() -> LocalTrack {
+    LocalTrack {
+        path: PathBuf::from("Daft Punk/Discovery/04 Harder Better Faster Stronger.flac"),
+        isrc: None,
+        title: Some("Harder, Better, Faster, Stronger".to_string()),
+        artist: Some("Daft Punk".to_string()),
+        album: Some("Discovery".to_string()),
+        duration: Some(226),
+    }
+}
+
+fn search_result(
+    id: u64,
+    title: &str,
+    artist: &str,
+    album: &str,
+    duration: u64,
+) -> models::api::Track {
+    let mut track = models::api::Track {
+        id: TrackId(id),
+        title: title.to_string(),
+        title_short: title.to_string(),
+        duration,
+        ..Default::default()
+    };
+    track.artist.name = artist.to_string();
+    track.album.title = album.to_string();
+    track
+}
+
+#[test]
+fn test_library_score() {
+    let local = local_track();
+    let exact = Candidate::from(&search_result(
+        3135556,
+        "Harder, Better, Faster, Stronger",
+        "Daft Punk",
+        "Discovery",
+        224,
+    ));
+    assert_eq!(score(&local, &exact), 1.0);
+    let other = Candidate::from(&search_result(
+        1,
+        "Stronger",
+        "Kanye West",
+        "Graduation",
+        311,
+    ));
+    assert!(score(&local, &other) < 0.3);
+}
+
+#[test]
+fn test_library_match_from_search() {
+    let local = local_track();
+    let results = [
+        search_result(1, "Stronger", "Kanye West", "Graduation", 311),
+        search_result(
+            3135556,
+            "Harder, Better, Faster, Stronger",
+            "Daft Punk",
+            "Discovery",
+            224,
+        ),
+    ];
+    let file_match = FileMatch::from_search(&local, &results);
+    assert_eq!(file_match.status, MatchStatus::Matched);
+    assert_eq!(file_match.confidence, Some(Confidence::High));
+    assert_eq!(file_match.track_id, Some(TrackId(3135556)));
+    assert_eq!(file_match.candidates[0].track_id, TrackId(3135556));
+
+    // the same recording on two releases can not be told apart
+    let results = [
+        search_result(
+            3135556,
+            "Harder, Better, Faster, Stronger",
+            "Daft Punk",
+            "Discovery",
+            224,
+        ),
+        search_result(
+            3129407,
+            "Harder, Better, Faster, Stronger",
+            "Daft Punk",
+            "Alive 2007",
+            225,
+        ),
+    ];
+    let local = LocalTrack {
+        album: None,
+        ..local_track()
+    };
+    let file_match = FileMatch::from_search(&local, &results);
+    assert_eq!(file_match.status, MatchStatus::Ambiguous);
+    assert_eq!(file_match.track_id, None);
+    assert_eq!(file_match.candidates.len(), 2);
+
+    let file_match = FileMatch::from_search(&local, &[]);
+    assert_eq!(file_match.status, MatchStatus::Unmatched);
+    assert_eq!(file_match.confidence, None);
+}
+
+#[test]
+fn test_library_search_query() {
+    let local = LocalTrack {
+        title: Some("Rock & Roll \"Live\"".to_string()),
+        ..local_track()
+    };
+    assert_eq!(
+        local.search_query().unwrap(),
+        r#"track:"Rock   Roll  Live" artist:"Daft Punk""#
+    );
+    assert_eq!(
+        local.loose_search_query().unwrap(),
+        "Daft Punk Rock   Roll  Live"
+    );
+    assert!(LocalTrack::default().search_query().is_none());
+}
+
+#[test]
+fn test_library_report_resume() {
+    let dir = temp_dir("library");
+    fs::create_dir_all(dir.join("Discovery")).unwrap();
+    fs::write(dir.join("Discovery/01.mp3"), b"").unwrap();
+    fs::write(dir.join("Discovery/cover.jpg"), b"").unwrap();
+    fs::write(dir.join("02.flac"), b"").unwrap();
+    let files = audio_files(&dir).unwrap();
+    assert_eq!(files, [dir.join("02.flac"), dir.join("Discovery/01.mp3")]);
+
+    let report_path = dir.join("report.json");
+    let mut report = MatchReport::load(&report_path).unwrap();
+    assert!(report.files.is_empty());
+    let first = LocalTrack {
+        path: files[0].clone(),
+        ..local_track()
+    };
+    let second = LocalTrack {
+        path: files[1].clone(),
+        ..local_track()
+    };
+    report.insert(FileMatch::from_search(&first, &[]));
+    report.insert(FileMatch::failed(&second, "timeout".to_string()));
+    report.save(&report_path).unwrap();
+
+    let mut report = MatchReport::load(&report_path).unwrap();
+    assert!(report.is_done(&files[0]));
+    assert!(!report.is_done(&files[1]));
+    assert_eq!(report.unmatched().count(), 1);
+    assert_eq!(report.failed().count(), 1);
+    report.insert(FileMatch::from_search(
+        &second,
+        &[search_result(
+            3135556,
+            "Harder, Better, Faster, Stronger",
+            "Daft Punk",
+            "Discovery",
+            224,
+        )],
+    ));
+    assert_eq!(report.files.len(), 2);
+    assert_eq!(report.matched().count(), 1);
+    assert_eq!(report.failed().count(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_library_audio_files_symlink_cycle() {
+    let dir = temp_dir("symlinks");
+    fs::remove_dir_all(&dir).unwrap();
+    fs::create_dir_all(dir.join("Discovery")).unwrap();
+    fs::write(dir.join("Discovery/01.mp3"), b"").unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("Discovery/library")).unwrap();
+    assert_eq!(audio_files(&dir).unwrap(), [dir.join("Discovery/01.mp3")]);
+}
+
+#[test]
+fn test_library_report_index() {
+    let mut report = MatchReport::default();
+    for i in 0..3 {
+        let local = LocalTrack {
+            path: PathBuf::from(format!("{i:02}.flac")),
+            ..local_track()
+        };
+        report.insert(FileMatch::failed(&local, "timeout".to_string()));
+    }
+    report.insert(FileMatch::from_search(&local_track(), &[]));
+    assert_eq!(report.files.len(), 4);
+    assert!(report.is_done(&local_track().path));
+
+    // the index follows changes made to files directly
+    report.files.remove(0);
+    assert!(report.get(&PathBuf::from("00.flac")).is_none());
+    assert!(report.get(&PathBuf::from("02.flac")).is_some());
+    let local = LocalTrack {
+        path: PathBuf::from("02.flac"),
+        ..local_track()
+    };
+    report.insert(FileMatch::from_search(&local, &[]));
+    assert_eq!(report.files.len(), 3);
+    assert!(report.is_done(&local.path));
+}
+
+#[tokio::test]
+async fn test_library_match_isrc_errors() {
+    let local = LocalTrack {
+        isrc: Some("GBDUW0000059".to_string()),
+        title: None,
+        ..local_track()
+    };
+    // an unknown isrc falls back to searching, which has no query here
+    let transport = MockTransport::new().with_api(
+        "track/isrc:GBDUW0000059",
+        r#"{"error":{"type":"DataException","message":"no data","code":800}}"#,
+    );
+    let deezer = Deezer::new().with_transport(transport);
+    let file_match = deezer.match_local_track(&local).await;
+    assert_eq!(file_match.status, MatchStatus::Unmatched);
+    assert!(file_match.error.is_none());
+
+    let deezer = Deezer::new().with_transport(MockTransport::new());
+    let file_match = deezer.match_local_track(&local).await;
+    assert_eq!(file_match.status, MatchStatus::Failed);
+    assert!(file_match.error.unwrap().contains("TransportError"));
+}
+
+#[tokio::test]
+async fn test_library_match_first_page() {
+    let track = search_result(
+        3135556,
+        "Harder, Better, Faster, Stronger",
+        "Daft Punk",
+        "Discovery",
+        224,
+    );
+    let page = serde_json::json!({
+        "data": [track],
+        "total": 300,
+        "next": "https://api.deezer.com/search?q=daft&strict=off&limit=10&index=10",
+    });
+    let transport = Arc::new(MockTransport::new().with_api("search?limit=10", page.to_string()));
+    let deezer = Deezer::new().with_transport(transport.clone());
+    let file_match = deezer.match_local_track(&local_track()).await;
+    assert_eq!(file_match.track_id, Some(TrackId(3135556)));
+    // the next page is never requested
+    assert_eq!(transport.requests().len(), 1);
+}
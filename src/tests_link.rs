@@ -0,0 +1,197 @@
+use crate::link::{api_url_path, parse, DeezerResource, LinkError, Resolved};
+use crate::models::ids::{AlbumId, ArtistId, PlaylistId, TrackId};
+use crate::transport::{HttpResponse, MockTransport};
+use crate::{blocking, Deezer, DeezerError};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const RADIO: &str = r#"{"id":37151,"title":"Hits","picture":"","picture_small":"","picture_medium":"","picture_big":"","picture_xl":"","tracklist":"","md5_image":"","type":"radio"}"#;
+const USER: &str = r#"{"id":5,"name":"Daniel","country":"FR","tracklist":"","type":"user"}"#;
+
+#[test]
+fn test_parse() {
+    let links = [
+        (
+            "https://www.deezer.com/en/album/302127",
+            DeezerResource::Album(AlbumId(302127)),
+        ),
+        (
+            "deezer.com/track/3135556",
+            DeezerResource::Track(TrackId(3135556)),
+        ),
+        (
+            "http://www.deezer.com/pt-br/artist/27?utm_source=x#top",
+            DeezerResource::Artist(ArtistId(27)),
+        ),
+        (
+            "https://www.deezer.com/fr/playlist/908622995/",
+            DeezerResource::Playlist(PlaylistId(908622995)),
+        ),
+        ("https://www.deezer.com/profile/5", DeezerResource::User(5)),
+        (
+            "https://www.deezer.com/show/5781",
+            DeezerResource::Podcast(5781),
+        ),
+        (
+            "https://www.deezer.com/episode/12345",
+            DeezerResource::Episode(12345),
+        ),
+        (
+            "deezer://www.deezer.com/radio/37151",
+            DeezerResource::Radio(37151),
+        ),
+        (
+            "deezer://track/3135556",
+            DeezerResource::Track(TrackId(3135556)),
+        ),
+        (
+            "https://api.deezer.com/album/302127",
+            DeezerResource::Album(AlbumId(302127)),
+        ),
+    ];
+    for (url, resource) in links {
+        assert_eq!(parse(url), Ok(resource), "{url}");
+    }
+    assert_eq!(
+        "https://deezer.page.link/abc".parse::<DeezerResource>(),
+        Err(LinkError::ShortLink(
+            "https://deezer.page.link/abc".to_string()
+        ))
+    );
+    assert_eq!(
+        parse("https://open.spotify.com/track/1"),
+        Err(LinkError::UnsupportedHost("open.spotify.com".to_string()))
+    );
+    assert!(matches!(
+        parse("https://www.deezer.com/en/channels/explore"),
+        Err(LinkError::UnknownResource(_))
+    ));
+    assert!(matches!(
+        parse("ftp://deezer.com/track/1"),
+        Err(LinkError::InvalidUrl(_))
+    ));
+    assert_eq!(DeezerResource::User(5).api_path(), "user/5");
+    assert_eq!(
+        DeezerResource::Podcast(5781).to_string(),
+        "https://www.deezer.com/show/5781"
+    );
+}
+
+#[tokio::test]
+async fn test_resolve() {
+    let transport = MockTransport::new()
+        .with_url_response(
+            "https://deezer.page.link/abc",
+            HttpResponse::new(302, "")
+                .with_header("Location", "https://www.deezer.com/en/radio/37151"),
+        )
+        .with_url_response(
+            "https://deezer.page.link/loop",
+            HttpResponse::new(302, "").with_header("location", "/loop"),
+        )
+        .with_url_response("https://link.deezer.com/s/app", HttpResponse::ok(""))
+        .with_api("radio/37151", RADIO);
+    let deezer = Deezer::new().with_transport(transport);
+    let resolved = deezer.resolve("deezer.page.link/abc").await.unwrap();
+    assert!(matches!(resolved, Resolved::Radio(radio) if radio.title == "Hits"));
+    assert!(matches!(
+        deezer.resolve("https://deezer.page.link/loop").await,
+        Err(DeezerError::LinkError(LinkError::TooManyRedirects(_)))
+    ));
+    assert!(matches!(
+        deezer.resolve_link("https://link.deezer.com/s/app").await,
+        Err(DeezerError::LinkError(LinkError::ShortLink(_)))
+    ));
+    // no redirect but the transport followed it
+    let transport = MockTransport::new().with_url_response(
+        "https://link.deezer.com/s/app",
+        HttpResponse {
+            url: Some("https://www.deezer.com/fr/track/3135556".to_string()),
+            ..HttpResponse::ok("")
+        },
+    );
+    let deezer = Deezer::new().with_transport(transport);
+    assert_eq!(
+        deezer
+            .resolve_link("https://link.deezer.com/s/app")
+            .await
+            .unwrap(),
+        DeezerResource::Track(TrackId(3135556))
+    );
+}
+
+#[test]
+fn test_resolve_blocking() {
+    let transport = MockTransport::new().with_api("user/5", USER);
+    let deezer = blocking::Deezer::new().with_transport(transport);
+    let resolved = deezer.resolve("https://www.deezer.com/en/profile/5");
+    assert!(matches!(resolved, Ok(Resolved::User(user)) if user.name == "Daniel"));
+    let err = deezer
+        .resolve("https://www.deezer.com/en/album/1")
+        .unwrap_err();
+    assert_eq!(err.kind(), "transport");
+}
+
+fn track(id: u64) -> Value {
+    json!({
+        "id": id,
+        "readable": true,
+        "title": "One More Time",
+        "title_short": "One More Time",
+        "duration": 320,
+        "rank": 0,
+        "explicit_lyrics": false,
+        "explicit_content_lyrics": 0,
+        "explicit_content_cover": 0,
+        "preview": "",
+        "md5_image": "",
+        "artist": {"id": 27, "name": "Daft Punk", "tracklist": "", "type": "artist"},
+        "type": "track"
+    })
+}
+
+#[test]
+fn test_api_url_path() {
+    assert_eq!(
+        api_url_path("https://api.deezer.com/artist/27/top?limit=50").unwrap(),
+        "artist/27/top?limit=50"
+    );
+    assert_eq!(
+        api_url_path("https://www.deezer.com/album/302127/tracks"),
+        Err(LinkError::UnsupportedHost("www.deezer.com".to_string()))
+    );
+    assert!(matches!(
+        api_url_path("album/302127/tracks"),
+        Err(LinkError::InvalidUrl(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_tracklist() {
+    let first = json!({
+        "data": [track(3135553), track(3135554)],
+        "total": 3,
+        "next": "https://api.deezer.com/artist/27/top?limit=2&index=2"
+    });
+    let second = json!({"data": [track(3135555)], "total": 3});
+    let transport = Arc::new(
+        MockTransport::new()
+            .with_api("artist/27/top?index=2", second.to_string())
+            .with_api("artist/27/top?limit=2", first.to_string()),
+    );
+    let deezer = Deezer::new().with_transport(transport.clone());
+    let tracks = deezer
+        .tracklist("https://api.deezer.com/artist/27/top?limit=2")
+        .await
+        .unwrap();
+    let ids: Vec<TrackId> = tracks.iter().map(|t| t.id).collect();
+    assert_eq!(ids, [3135553, 3135554, 3135555].map(TrackId));
+    assert_eq!(transport.requests().len(), 2);
+
+    let err = deezer
+        .tracklist("https://example.com/artist/27/top")
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), "link");
+    assert_eq!(transport.requests().len(), 2);
+}
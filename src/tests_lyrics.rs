@@ -0,0 +1,185 @@
+use crate::lyrics::{SyncedLine, SyncedLyrics};
+use crate::models;
+use serde_json::json;
+
+fn sample_lyrics() -> SyncedLyrics {
+    let lyrics: models::gw::Lyrics = serde_json::from_value(json!({
+        "LYRICS_ID": "2780622",
+        "LYRICS_SYNC_JSON": [
+            {"lrc_timestamp": "[00:14.05]", "milliseconds": "14050", "duration": "1800", "line": "Make it better"},
+            {"line": ""},
+            {"lrc_timestamp": "[00:12.34]", "milliseconds": "12340", "duration": "1710", "line": "Work it harder"},
+            {"lrc_timestamp": "[00:16.20]", "line": "Do it faster"}
+        ],
+        "LYRICS_TEXT": "Work it harder\nMake it better\nDo it faster",
+        "LYRICS_COPYRIGHTS": "",
+        "LYRICS_WRITERS": ""
+    }))
+    .unwrap();
+    SyncedLyrics::from(&lyrics)
+}
+
+#[test]
+fn test_synced_lyrics_from_gw() {
+    let lyrics = sample_lyrics();
+    assert_eq!(
+        lyrics.lines,
+        [
+            SyncedLine {
+                start: 12_340,
+                duration: Some(1710),
+                text: "Work it harder".to_string()
+            },
+            SyncedLine {
+                start: 14_050,
+                duration: Some(1800),
+                text: "Make it better".to_string()
+            },
+            SyncedLine {
+                start: 16_200,
+                duration: None,
+                text: "Do it faster".to_string()
+            },
+        ]
+    );
+    assert_eq!(lyrics.line_end(0), Some(14_050));
+    assert_eq!(lyrics.line_end(2), Some(20_200));
+}
+
+#[test]
+fn test_synced_lyrics_bad_milliseconds() {
+    let lyrics: models::gw::Lyrics = serde_json::from_value(json!({
+        "LYRICS_ID": "2780622",
+        "LYRICS_SYNC_JSON": [
+            {"lrc_timestamp": "[00:18.00]", "milliseconds": "", "line": "Makes us stronger"},
+            {"milliseconds": "n/a", "line": "More than ever"}
+        ],
+        "LYRICS_TEXT": "Makes us stronger\nMore than ever",
+        "LYRICS_COPYRIGHTS": "",
+        "LYRICS_WRITERS": ""
+    }))
+    .unwrap();
+    // the lrc timestamp is used instead, a line with neither is skipped
+    let lyrics = SyncedLyrics::from(&lyrics);
+    assert_eq!(
+        lyrics.lines,
+        [SyncedLine {
+            start: 18_000,
+            duration: None,
+            text: "Makes us stronger".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_synced_lyrics_lrc_round_trip() {
+    let lyrics = SyncedLyrics {
+        title: Some("Harder, Better, Faster, Stronger".to_string()),
+        artist: Some("Daft Punk".to_string()),
+        album: Some("Discovery".to_string()),
+        ..sample_lyrics()
+    };
+    let lrc = lyrics.to_lrc();
+    assert!(lrc.starts_with(
+        "[ti:Harder, Better, Faster, Stronger]\n[ar:Daft Punk]\n[al:Discovery]\n[00:12.34]Work it harder\n"
+    ));
+    let parsed = SyncedLyrics::from_lrc(&lrc);
+    assert_eq!(parsed.title, lyrics.title);
+    assert_eq!(parsed.artist, lyrics.artist);
+    assert_eq!(parsed.album, lyrics.album);
+    let timings: Vec<(u64, &str)> = parsed
+        .lines
+        .iter()
+        .map(|l| (l.start, l.text.as_str()))
+        .collect();
+    assert_eq!(
+        timings,
+        [
+            (12_340, "Work it harder"),
+            (14_050, "Make it better"),
+            (16_200, "Do it faster")
+        ]
+    );
+}
+
+#[test]
+fn test_synced_lyrics_lrc_repeated_timestamps_and_offset() {
+    let parsed = SyncedLyrics::from_lrc("[offset:+500]\n[00:20.5][00:01.000]Around the world\n");
+    let timings: Vec<u64> = parsed.lines.iter().map(|l| l.start).collect();
+    assert_eq!(timings, [500, 20_000]);
+    assert!(parsed.lines.iter().all(|l| l.text == "Around the world"));
+}
+
+#[test]
+fn test_synced_lyrics_srt_and_webvtt() {
+    let lyrics = sample_lyrics();
+    assert_eq!(
+        lyrics.to_srt(),
+        "1\n00:00:12,340 --> 00:00:14,050\nWork it harder\n\n\
+         2\n00:00:14,050 --> 00:00:15,850\nMake it better\n\n\
+         3\n00:00:16,200 --> 00:00:20,200\nDo it faster\n\n"
+    );
+    let vtt = lyrics.to_webvtt();
+    assert!(vtt.starts_with("WEBVTT\n\n00:00:12.340 --> 00:00:14.050\nWork it harder\n\n"));
+}
+
+#[test]
+fn test_synced_lyrics_ass_karaoke() {
+    let ass = sample_lyrics().to_ass();
+    assert!(ass.contains("[Events]"));
+    let dialogues: Vec<&str> = ass.lines().filter(|l| l.starts_with("Dialogue:")).collect();
+    assert_eq!(dialogues.len(), 3);
+    assert!(dialogues[0].starts_with("Dialogue: 0,0:00:12.34,0:00:14.05,Default,"));
+    // the \k durations of a line add up to the line duration in centiseconds
+    let total: u64 = dialogues[0]
+        .split("{\\k")
+        .skip(1)
+        .map(|part| part.split('}').next().unwrap().parse::<u64>().unwrap())
+        .sum();
+    assert_eq!(total, 171);
+}
+
+#[test]
+fn test_synced_lyrics_line_at() {
+    let lyrics = sample_lyrics();
+    assert_eq!(lyrics.line_index_at(0), None);
+    assert_eq!(lyrics.line_index_at(12_339), None);
+    assert_eq!(lyrics.line_index_at(12_340), Some(0));
+    assert_eq!(lyrics.line_index_at(15_000), Some(1));
+    assert_eq!(
+        lyrics.line_at(60_000).map(|l| l.text.as_str()),
+        Some("Do it faster")
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_lyrics_cursor_follows_playback() {
+    let mut cursor = sample_lyrics().cursor(13_000);
+    let start = tokio::time::Instant::now();
+    // the line already active at the start position comes first, right away
+    assert_eq!(cursor.next_line().await.unwrap().0, 0);
+    assert_eq!(start.elapsed().as_millis(), 0);
+    let (index, line) = cursor.next_line().await.unwrap();
+    assert_eq!((index, line.text.as_str()), (1, "Make it better"));
+    assert_eq!(start.elapsed().as_millis(), 1050);
+    assert_eq!(cursor.current_index(), Some(1));
+    cursor.next_line().await.unwrap();
+    assert!(cursor.next_line().await.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_lyrics_cursor_seek_and_offset() {
+    let mut cursor = sample_lyrics().cursor(0);
+    cursor.seek(14_500);
+    assert_eq!(cursor.current_index(), Some(1));
+    assert_eq!(cursor.next_line().await.unwrap().0, 1);
+    cursor.seek(0);
+    assert_eq!(cursor.current_index(), None);
+    // lines shown 500ms later
+    cursor.set_offset(500);
+    let start = tokio::time::Instant::now();
+    assert_eq!(cursor.next_line().await.unwrap().0, 0);
+    assert_eq!(start.elapsed().as_millis(), 12_840);
+    cursor.set_offset(-2000);
+    assert_eq!(cursor.current_index(), Some(1));
+}
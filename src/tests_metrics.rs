@@ -0,0 +1,85 @@
+use crate::metrics::{api_path_template, QUOTA_LIMIT};
+use crate::models::ids::{ArtistId, TrackId};
+use crate::transport::{HttpResponse, MockTransport};
+use crate::{blocking, Deezer};
+
+const ARTIST: &str = r#"{"id":27,"name":"Daft Punk","tracklist":"","type":"artist"}"#;
+
+#[test]
+fn test_api_path_template() {
+    assert_eq!(api_path_template("track/3135556"), "track/{id}");
+    assert_eq!(
+        api_path_template("artist/27/top?limit=50"),
+        "artist/{id}/top"
+    );
+    assert_eq!(
+        api_path_template("track/isrc:GBDUW0000059"),
+        "track/isrc:{isrc}"
+    );
+    assert_eq!(
+        api_path_template("album/upc:724384960650"),
+        "album/upc:{upc}"
+    );
+    assert_eq!(api_path_template("search/track"), "search/track");
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let transport = MockTransport::new()
+        .with_api("artist/27", ARTIST)
+        .with_api("artist/1", ARTIST)
+        .with_api("artist/2", r#"{"error":{"type":"DataException"}}"#)
+        .with_api_response("artist/3", HttpResponse::new(503, "unavailable"))
+        .with_gw("deezer.getUserData", "{}");
+    let deezer = Deezer::new().with_transport(transport);
+    for id in [27, 1, 2, 3, 4].map(ArtistId) {
+        let _ = deezer.artist(id).await;
+    }
+    let _ = deezer.gw_song(TrackId(3135556)).await;
+
+    let metrics = deezer.clone().metrics();
+    let artist = metrics.endpoint("api:artist/{id}").unwrap();
+    assert_eq!(artist.statuses.get(&200), Some(&3));
+    assert_eq!(artist.statuses.get(&503), Some(&1));
+    assert_eq!(artist.responses(), 4);
+    assert_eq!(artist.errors.get("api"), Some(&1));
+    assert_eq!(artist.errors.get("json"), Some(&1));
+    assert_eq!(artist.errors.get("transport"), Some(&1));
+    assert_eq!(artist.latency.count, 5);
+    assert_eq!(artist.latency.buckets.iter().sum::<u64>(), 5);
+    let gw = metrics.endpoint("gw:song.getData").unwrap();
+    assert_eq!(gw.errors.get("transport"), Some(&1));
+    assert!(metrics.endpoint("gw:deezer.getUserData").is_some());
+    // gw requests do not count against the api quota
+    assert_eq!((metrics.quota_used, metrics.quota_limit), (5, QUOTA_LIMIT));
+    assert_eq!(metrics.quota_usage(), 0.1);
+
+    let text = metrics.to_prometheus();
+    assert!(
+        text.contains("deezer_responses_total{endpoint=\"api:artist/{id}\",status=\"200\"} 3\n")
+    );
+    assert!(
+        text.contains("deezer_errors_total{endpoint=\"gw:song.getData\",kind=\"transport\"} 1\n")
+    );
+    assert!(text.contains(
+        "deezer_request_duration_seconds_bucket{endpoint=\"api:artist/{id}\",le=\"+Inf\"} 5\n"
+    ));
+    assert!(
+        text.contains("deezer_request_duration_seconds_count{endpoint=\"api:artist/{id}\"} 5\n")
+    );
+    assert!(text.contains("deezer_quota_used 5\n"));
+
+    deezer.reset_metrics();
+    let metrics = deezer.metrics();
+    assert!(metrics.endpoints.is_empty());
+    assert_eq!(metrics.quota_used, 5);
+}
+
+#[test]
+fn test_metrics_blocking() {
+    let transport = MockTransport::new().with_api("artist/27", ARTIST);
+    let deezer = blocking::Deezer::new().with_transport(transport);
+    deezer.artist(ArtistId(27)).unwrap();
+    let metrics = deezer.metrics();
+    assert_eq!(metrics.endpoint("api:artist/{id}").unwrap().responses(), 1);
+}
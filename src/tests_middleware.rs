@@ -0,0 +1,102 @@
+use crate::middleware::Middleware;
+use crate::models::ids::{ArtistId, TrackId};
+use crate::transport::{HttpRequest, HttpResponse, MockTransport};
+use crate::{blocking, Deezer, DeezerError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ARTIST: &str = r#"{"id":27,"name":"Daft Punk","tracklist":"","type":"artist"}"#;
+
+#[derive(Debug, Default)]
+struct Recorder {
+    calls: Mutex<Vec<String>>,
+}
+
+impl Recorder {
+    fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl Middleware for Recorder {
+    fn before_request(&self, request: &mut HttpRequest) {
+        let endpoint = request.endpoint().unwrap_or_default();
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("before {endpoint}"));
+    }
+
+    fn after_response(&self, request: &HttpRequest, response: &HttpResponse, _: Duration) {
+        let endpoint = request.endpoint().unwrap_or_default();
+        let call = format!("after {endpoint} {} {}", response.status, response.text());
+        self.calls.lock().unwrap().push(call);
+    }
+
+    fn on_error(&self, request: &HttpRequest, error: &DeezerError, _: Duration) {
+        let endpoint = request.endpoint().unwrap_or_default();
+        let kind = match error {
+            DeezerError::TransportError(_) => "transport",
+            _ => "other",
+        };
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("error {endpoint} {kind}"));
+    }
+}
+
+// Adds a sig parameter to every request
+#[derive(Debug)]
+struct Signer;
+
+impl Middleware for Signer {
+    fn before_request(&self, request: &mut HttpRequest) {
+        request.url.query_pairs_mut().append_pair("sig", "signed");
+    }
+}
+
+#[tokio::test]
+async fn test_middleware_hooks() {
+    let transport = Arc::new(MockTransport::new().with_api("artist/27", ARTIST));
+    let recorder = Arc::new(Recorder::default());
+    let deezer = Deezer::new()
+        .with_transport(transport.clone())
+        .with_middleware(Signer)
+        .with_middleware(recorder.clone());
+    deezer.artist(ArtistId(27)).await.unwrap();
+    assert!(deezer.artist(ArtistId(13)).await.is_err());
+    assert_eq!(
+        recorder.calls(),
+        [
+            "before artist".to_string(),
+            format!("after artist 200 {ARTIST}"),
+            "before artist".to_string(),
+            "error artist transport".to_string(),
+        ]
+    );
+    let requests = transport.requests();
+    assert_eq!(requests[0].param("sig").as_deref(), Some("signed"));
+    assert!(requests[1]
+        .params()
+        .contains(&("limit".to_string(), "100".to_string())));
+}
+
+#[test]
+fn test_middleware_blocking() {
+    let transport = MockTransport::new().with_gw("deezer.getUserData", "{}");
+    let recorder = Arc::new(Recorder::default());
+    let deezer = blocking::Deezer::new()
+        .with_transport(transport)
+        .with_middleware(recorder.clone());
+    assert!(deezer.gw_song(TrackId(3135556)).is_err());
+    assert_eq!(
+        recorder.calls(),
+        [
+            "before deezer.getUserData",
+            "after deezer.getUserData 200 {}",
+            "before song.getData",
+            "error song.getData transport",
+        ]
+    );
+}
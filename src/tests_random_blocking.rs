@@ -1,4 +1,5 @@
 use crate::blocking::Deezer;
+use crate::models::ids::{AlbumId, ArtistId, TrackId};
 use crate::tests_blocking::print_errors_for_items;
 use rand::distributions::uniform::SampleRange;
 use rand::Rng;
@@ -14,7 +15,7 @@ fn gen_three_rand_nums<T: SampleRange<u64> + Clone>(range: T) -> [u64; 3] {
 
 #[test]
 fn rand_gw_track() {
-    let song_ids = gen_three_rand_nums(250_000..=350_000);
+    let song_ids = gen_three_rand_nums(250_000..=350_000).map(TrackId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.gw_track(song_ids[0]),
@@ -27,7 +28,7 @@ fn rand_gw_track() {
 
 #[test]
 fn rand_gw_song() {
-    let song_ids = gen_three_rand_nums(250_000..=350_000);
+    let song_ids = gen_three_rand_nums(250_000..=350_000).map(TrackId);
     let deezer = Deezer::new();
     let songs = vec![
         deezer.gw_song(song_ids[0]),
@@ -40,7 +41,7 @@ fn rand_gw_song() {
 
 #[test]
 fn rand_gw_songs() {
-    let song_ids = gen_three_rand_nums(250_000..=350_000).to_vec();
+    let song_ids = gen_three_rand_nums(250_000..=350_000).map(TrackId).to_vec();
     let deezer = Deezer::new();
     let song_list = deezer.gw_songs(&song_ids);
     if let Err(error) = &song_list {
@@ -51,7 +52,7 @@ fn rand_gw_songs() {
 
 #[test]
 fn rand_gw_songs_from_album() {
-    let album_ids = gen_three_rand_nums(1..=100_000);
+    let album_ids = gen_three_rand_nums(1..=100_000).map(AlbumId);
     let deezer = Deezer::new();
     let song_list = vec![
         deezer.gw_songs_from_album(album_ids[0]),
@@ -64,7 +65,7 @@ fn rand_gw_songs_from_album() {
 
 #[test]
 fn rand_gw_album() {
-    let album_ids = gen_three_rand_nums(1..=100_000);
+    let album_ids = gen_three_rand_nums(1..=100_000).map(AlbumId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.gw_album(album_ids[0]),
@@ -77,7 +78,7 @@ fn rand_gw_album() {
 
 #[test]
 fn rand_gw_lyrics() {
-    let song_ids = gen_three_rand_nums(250_000..=350_000);
+    let song_ids = gen_three_rand_nums(250_000..=350_000).map(TrackId);
     let deezer = Deezer::new();
     let lyrics = vec![
         deezer.gw_lyrics(song_ids[0]),
@@ -91,7 +92,7 @@ fn rand_gw_lyrics() {
 /*
 #[test]
 fn rand_gw_artist() {
-    let artist_ids = gen_three_rand_nums(0..=30_000);
+    let artist_ids = gen_three_rand_nums(0..=30_000).map(ArtistId);
     let deezer = Deezer::new();
     let artists = vec![
         deezer.gw_artist(artist_ids[0], "en"),
@@ -105,7 +106,7 @@ fn rand_gw_artist() {
 
 #[test]
 fn rand_track() {
-    let song_ids = gen_three_rand_nums(250_000..=350_000);
+    let song_ids = gen_three_rand_nums(250_000..=350_000).map(TrackId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.track(song_ids[0]),
@@ -118,7 +119,7 @@ fn rand_track() {
 
 #[test]
 fn rand_album() {
-    let album_ids = gen_three_rand_nums(1..=100_000);
+    let album_ids = gen_three_rand_nums(1..=100_000).map(AlbumId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.album(album_ids[0]),
@@ -131,7 +132,7 @@ fn rand_album() {
 
 #[test]
 fn rand_album_tracks() {
-    let album_ids = gen_three_rand_nums(1..=100_000);
+    let album_ids = gen_three_rand_nums(1..=100_000).map(AlbumId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.album_tracks(album_ids[0]),
@@ -144,7 +145,7 @@ fn rand_album_tracks() {
 
 #[test]
 fn rand_artist() {
-    let artist_ids = gen_three_rand_nums(1..=50_000);
+    let artist_ids = gen_three_rand_nums(1..=50_000).map(ArtistId);
     let deezer = Deezer::new();
     let artists = vec![
         deezer.artist(artist_ids[0]),
@@ -157,7 +158,7 @@ fn rand_artist() {
 
 #[test]
 fn rand_artist_albums() {
-    let artist_ids = gen_three_rand_nums(1..=50_000);
+    let artist_ids = gen_three_rand_nums(1..=50_000).map(ArtistId);
     let deezer = Deezer::new();
     let albums = vec![
         deezer.artist_albums(artist_ids[0]),
@@ -170,7 +171,7 @@ fn rand_artist_albums() {
 
 #[test]
 fn rand_artist_top_tracks() {
-    let artist_ids = gen_three_rand_nums(1..=50_000);
+    let artist_ids = gen_three_rand_nums(1..=50_000).map(ArtistId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.artist_top_tracks(artist_ids[0]),
@@ -183,7 +184,7 @@ fn rand_artist_top_tracks() {
 
 #[test]
 fn rand_artist_related_artists() {
-    let artist_ids = gen_three_rand_nums(1..=50_000);
+    let artist_ids = gen_three_rand_nums(1..=50_000).map(ArtistId);
     let deezer = Deezer::new();
     let artists = vec![
         deezer.artist_related_artists(artist_ids[0]),
@@ -196,7 +197,7 @@ fn rand_artist_related_artists() {
 
 #[test]
 fn rand_artist_radio() {
-    let artist_ids = gen_three_rand_nums(1..=50_000);
+    let artist_ids = gen_three_rand_nums(1..=50_000).map(ArtistId);
     let deezer = Deezer::new();
     let tracks = vec![
         deezer.artist_radio(artist_ids[0]),
@@ -209,7 +210,7 @@ fn rand_artist_radio() {
 
 #[test]
 fn rand_artist_playlists() {
-    let artist_ids = gen_three_rand_nums(1..=50_000);
+    let artist_ids = gen_three_rand_nums(1..=50_000).map(ArtistId);
     let deezer = Deezer::new();
     let playlists = vec![
         deezer.artist_playlists(artist_ids[0]),
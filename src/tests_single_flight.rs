@@ -0,0 +1,111 @@
+use crate::single_flight::{ShareError, SingleFlight};
+use crate::DeezerError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn test_single_flight_shares_result() {
+    let flights: SingleFlight<String, String> = SingleFlight::default();
+    let calls = AtomicUsize::new(0);
+    let fetch = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok::<_, String>("album/302127".to_string())
+    };
+    let (a, b, c) = tokio::join!(
+        flights.run("api:album/302127", fetch),
+        flights.run("api:album/302127", fetch),
+        flights.run("api:album/302127", fetch),
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    for result in [a, b, c] {
+        assert_eq!(result.unwrap(), "album/302127");
+    }
+    assert_eq!(flights.in_flight(), 0);
+
+    // once finished, the next call fetches again
+    flights.run("api:album/302127", fetch).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_single_flight_distinct_keys() {
+    let flights: SingleFlight<u64, String> = SingleFlight::default();
+    let calls = AtomicUsize::new(0);
+    let fetch = |id: u64| {
+        let calls = &calls;
+        move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok::<_, String>(id)
+        }
+    };
+    let (a, b) = tokio::join!(
+        flights.run("api:track/1", fetch(1)),
+        flights.run("api:track/2", fetch(2)),
+    );
+    assert_eq!((a, b), (Ok(1), Ok(2)));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_single_flight_shares_errors() {
+    let flights: SingleFlight<u64, String> = SingleFlight::default();
+    let calls = AtomicUsize::new(0);
+    let fetch = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Err::<u64, _>("timeout".to_string())
+    };
+    let (a, b, c) = tokio::join!(
+        flights.run("api:track/3135556", fetch),
+        flights.run("api:track/3135556", fetch),
+        flights.run("api:track/3135556", fetch),
+    );
+    for result in [a, b, c] {
+        assert_eq!(result, Err("timeout".to_string()));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(flights.in_flight(), 0);
+}
+
+#[test]
+fn test_single_flight_shared_deezer_errors() {
+    let json = serde_json::from_str::<u64>("{").unwrap_err();
+    for err in [
+        DeezerError::JsonError(json),
+        DeezerError::ApiError("no data".to_string()),
+        DeezerError::NotCached("api:track/1".to_string()),
+    ] {
+        let shared = err.share();
+        assert_eq!(shared.kind(), err.kind());
+        assert_eq!(format!("{shared:?}"), format!("{err:?}"));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_single_flight_cancelled() {
+    let flights: SingleFlight<u64, String> = SingleFlight::default();
+    let calls = AtomicUsize::new(0);
+    let fetch = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok::<_, String>(302127)
+    };
+    let timeout = Duration::from_millis(50);
+    let cancelled = tokio::time::timeout(timeout, flights.run("api:album/302127", fetch)).await;
+    assert!(cancelled.is_err());
+    assert_eq!(flights.in_flight(), 0);
+    assert_eq!(flights.run("api:album/302127", fetch).await, Ok(302127));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // a caller still waiting takes over from the cancelled leader
+    let (cancelled, waiting) = tokio::join!(
+        tokio::time::timeout(timeout, flights.run("api:album/302127", fetch)),
+        flights.run("api:album/302127", fetch),
+    );
+    assert!(cancelled.is_err());
+    assert_eq!(waiting, Ok(302127));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    assert_eq!(flights.in_flight(), 0);
+}
@@ -0,0 +1,311 @@
+use crate::models;
+use crate::tagging::{read_tags, write_id3, write_tags, Id3Version, Picture, TagSet};
+use ogg::{PacketWriteEndInfo, PacketWriter};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("deezerapi-rs-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn sample_tags() -> TagSet {
+    TagSet {
+        title: Some("Harder, Better, Faster, Stronger".to_string()),
+        artists: vec!["Daft Punk".to_string()],
+        album: Some("Discovery".to_string()),
+        album_artist: Some("Daft Punk".to_string()),
+        track_number: Some(4),
+        track_total: Some(14),
+        disc_number: Some(1),
+        disc_total: Some(1),
+        isrc: Some("GBDUW0000059".to_string()),
+        barcode: Some("0724384960650".to_string()),
+        label: Some("Parlophone (France)".to_string()),
+        copyright: Some("(P) 2001 Daft Life Ltd.".to_string()),
+        genres: vec!["Electro".to_string(), "Dance".to_string()],
+        release_date: Some("2001-03-07".to_string()),
+        bpm: Some(123.0),
+        gain: Some(-9.5),
+        explicit: Some(false),
+        contributors: vec![
+            ("composer".to_string(), "Thomas Bangalter".to_string()),
+            ("author".to_string(), "Edwin Birdsong".to_string()),
+        ],
+        cover: Some(Picture::jpeg(vec![0xff, 0xd8, 0xff, 0xe0, 1, 2, 3])),
+        lyrics: Some("Work it harder\nMake it better".to_string()),
+        synced_lyrics: vec![
+            (12_340, "Work it harder".to_string()),
+            (14_050, "Make it better".to_string()),
+        ],
+        lyrics_copyright: Some("Universal Music Publishing".to_string()),
+        ..Default::default()
+    }
+}
+
+fn assert_common_fields(read: &TagSet, expected: &TagSet) {
+    assert_eq!(read.title, expected.title);
+    assert_eq!(read.artists, expected.artists);
+    assert_eq!(read.album, expected.album);
+    assert_eq!(read.album_artist, expected.album_artist);
+    assert_eq!(read.track_number, expected.track_number);
+    assert_eq!(read.track_total, expected.track_total);
+    assert_eq!(read.disc_number, expected.disc_number);
+    assert_eq!(read.isrc, expected.isrc);
+    assert_eq!(read.barcode, expected.barcode);
+    assert_eq!(read.label, expected.label);
+    assert_eq!(read.copyright, expected.copyright);
+    assert_eq!(read.genres, expected.genres);
+    assert_eq!(read.release_date, expected.release_date);
+    assert_eq!(read.bpm, expected.bpm);
+    assert_eq!(read.gain, expected.gain);
+    assert_eq!(
+        read.contributors_with_role("composer"),
+        ["Thomas Bangalter"]
+    );
+    assert_eq!(read.contributors_with_role("author"), ["Edwin Birdsong"]);
+    assert_eq!(read.cover, expected.cover);
+    assert_eq!(read.lyrics, expected.lyrics);
+    assert_eq!(read.lyrics_copyright, expected.lyrics_copyright);
+}
+
+fn flac_file(sample_rate: u32, samples: u64, audio: &[u8]) -> Vec<u8> {
+    let mut streaminfo = vec![0u8; 34];
+    streaminfo[10] = (sample_rate >> 12) as u8;
+    streaminfo[11] = (sample_rate >> 4) as u8;
+    streaminfo[12] = ((sample_rate & 0x0f) << 4) as u8 | 0x02;
+    streaminfo[13] = 0xf0 | ((samples >> 32) & 0x0f) as u8;
+    streaminfo[14..18].copy_from_slice(&(samples as u32).to_be_bytes());
+    let mut file = b"fLaC".to_vec();
+    file.extend([0x80, 0, 0, 34]);
+    file.extend(streaminfo);
+    file.extend(audio);
+    file
+}
+
+fn ogg_file(sample_rate: u32, granule: u64) -> Vec<u8> {
+    let mut ident = b"\x01vorbis".to_vec();
+    ident.extend(0u32.to_le_bytes());
+    ident.push(2);
+    ident.extend(sample_rate.to_le_bytes());
+    ident.extend([0u8; 13]);
+    let mut comment = b"\x03vorbis".to_vec();
+    comment.extend(4u32.to_le_bytes());
+    comment.extend(b"test");
+    comment.extend(1u32.to_le_bytes());
+    comment.extend(13u32.to_le_bytes());
+    comment.extend(b"ENCODER=tests");
+    comment.push(1);
+    let mut writer = PacketWriter::new(Vec::new());
+    let packets = [
+        (ident, PacketWriteEndInfo::EndPage, 0),
+        (comment, PacketWriteEndInfo::NormalPacket, 0),
+        (b"\x05vorbis".to_vec(), PacketWriteEndInfo::EndPage, 0),
+        (vec![7u8; 64], PacketWriteEndInfo::EndStream, granule),
+    ];
+    for (data, end_info, absgp) in packets {
+        writer
+            .write_packet(data.into_boxed_slice(), 1234, end_info, absgp)
+            .unwrap();
+    }
+    writer.into_inner()
+}
+
+fn mp4_atom(kind: &[u8], content: &[u8]) -> Vec<u8> {
+    let mut atom = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+    atom.extend(kind);
+    atom.extend(content);
+    atom
+}
+
+// ftyp, moov with a single chunk offset into mdat, then mdat
+fn mp4_file(payload: &[u8]) -> Vec<u8> {
+    let ftyp = mp4_atom(b"ftyp", b"M4A \0\0\0\0M4A isom");
+    let mut mvhd = vec![0u8; 12];
+    mvhd.extend(1000u32.to_be_bytes());
+    mvhd.extend(215_000u32.to_be_bytes());
+    mvhd.extend([0u8; 80]);
+    let mvhd = mp4_atom(b"mvhd", &mvhd);
+    let build_moov = |offset: u32| {
+        let mut stco = vec![0u8; 4];
+        stco.extend(1u32.to_be_bytes());
+        stco.extend(offset.to_be_bytes());
+        let stbl = mp4_atom(b"stbl", &mp4_atom(b"stco", &stco));
+        let minf = mp4_atom(b"minf", &stbl);
+        let mdia = mp4_atom(b"mdia", &minf);
+        let trak = mp4_atom(b"trak", &mdia);
+        mp4_atom(b"moov", &[mvhd.clone(), trak].concat())
+    };
+    let moov_len = build_moov(0).len();
+    let offset = (ftyp.len() + moov_len + 8) as u32;
+    [ftyp, build_moov(offset), mp4_atom(b"mdat", payload)].concat()
+}
+
+fn mp4_chunk_offset(file: &[u8]) -> usize {
+    let pos = file.windows(4).position(|w| w == b"stco").unwrap();
+    u32::from_be_bytes(file[pos + 12..pos + 16].try_into().unwrap()) as usize
+}
+
+#[test]
+fn test_tags_mp3_id3v24() {
+    let path = temp_file("v24.mp3", &[0xff, 0xfb, 0x90, 0x64, 0, 0, 0, 0]);
+    let tags = sample_tags();
+    write_tags(&path, &tags).unwrap();
+    let read = read_tags(&path).unwrap();
+    assert_common_fields(&read, &tags);
+    assert_eq!(read.synced_lyrics, tags.synced_lyrics);
+    assert!(fs::read(&path)
+        .unwrap()
+        .ends_with(&[0xff, 0xfb, 0x90, 0x64, 0, 0, 0, 0]));
+}
+
+// Writes a partial TagSet over the sample tags
+fn assert_keeps_other_tags(path: &Path) {
+    write_tags(path, &sample_tags()).unwrap();
+    assert_eq!(read_tags(path).unwrap().explicit, Some(false));
+    let tags = TagSet {
+        title: Some("One More Time".to_string()),
+        explicit: Some(true),
+        ..Default::default()
+    };
+    write_tags(path, &tags).unwrap();
+    let read = read_tags(path).unwrap();
+    assert_eq!(read.title, tags.title);
+    assert_eq!(read.explicit, Some(true));
+    // fields without a value leave the existing tags alone
+    assert_eq!(read.album.as_deref(), Some("Discovery"));
+    assert_eq!(read.release_date.as_deref(), Some("2001-03-07"));
+    assert_eq!(read.barcode.as_deref(), Some("0724384960650"));
+    assert_eq!(
+        read.contributors_with_role("composer"),
+        ["Thomas Bangalter"]
+    );
+}
+
+#[test]
+fn test_tags_mp3_keeps_other_frames() {
+    assert_keeps_other_tags(&temp_file("partial.mp3", &[0xff, 0xfb, 0x90, 0x64]));
+}
+
+#[test]
+fn test_tags_flac_keeps_other_comments() {
+    let audio = [0xff, 0xf8, 0x69, 0x18, 0, 0];
+    assert_keeps_other_tags(&temp_file(
+        "partial.flac",
+        &flac_file(44_100, 44_100 * 215, &audio),
+    ));
+}
+
+#[test]
+fn test_tags_ogg_keeps_other_comments() {
+    assert_keeps_other_tags(&temp_file("partial.ogg", &ogg_file(48_000, 48_000 * 95)));
+}
+
+#[test]
+fn test_tags_m4a_keeps_other_items() {
+    assert_keeps_other_tags(&temp_file("partial.m4a", &mp4_file(b"audio payload")));
+}
+
+#[test]
+fn test_tags_mp3_id3v23() {
+    let path = temp_file("v23.mp3", &[0xff, 0xfb, 0x90, 0x64]);
+    let tags = TagSet {
+        genres: vec!["Electro".to_string()],
+        ..sample_tags()
+    };
+    write_id3(&path, &tags, Id3Version::Id3v23).unwrap();
+    let read = read_tags(&path).unwrap();
+    assert_eq!(read.release_date, Some("2001".to_string()));
+    assert_common_fields(
+        &read,
+        &TagSet {
+            release_date: Some("2001".to_string()),
+            ..tags
+        },
+    );
+}
+
+#[test]
+fn test_tags_mp3_id3v23_single_value() {
+    let path = temp_file("v23-artists.mp3", &[0xff, 0xfb, 0x90, 0x64]);
+    let tags = TagSet {
+        artists: vec!["AC/DC".to_string(), "Daft Punk".to_string()],
+        ..Default::default()
+    };
+    write_id3(&path, &tags, Id3Version::Id3v23).unwrap();
+    // v2.3 has no separator that can't appear in a name, the first artist is kept whole
+    assert_eq!(read_tags(&path).unwrap().artists, ["AC/DC"]);
+}
+
+#[test]
+fn test_tags_flac() {
+    let audio = [0xff, 0xf8, 0x69, 0x18, 0, 0];
+    let path = temp_file("track.flac", &flac_file(44_100, 44_100 * 215, &audio));
+    let tags = sample_tags();
+    write_tags(&path, &tags).unwrap();
+    // writing twice must replace, not duplicate, the managed comments
+    write_tags(&path, &tags).unwrap();
+    let read = read_tags(&path).unwrap();
+    assert_common_fields(&read, &tags);
+    assert_eq!(read.synced_lyrics, tags.synced_lyrics);
+    assert_eq!(read.duration, Some(215));
+    assert!(fs::read(&path).unwrap().ends_with(&audio));
+}
+
+#[test]
+fn test_tags_ogg_vorbis() {
+    let path = temp_file("track.ogg", &ogg_file(48_000, 48_000 * 95));
+    let tags = sample_tags();
+    write_tags(&path, &tags).unwrap();
+    let read = read_tags(&path).unwrap();
+    assert_common_fields(&read, &tags);
+    assert_eq!(read.synced_lyrics, tags.synced_lyrics);
+    assert_eq!(read.duration, Some(95));
+}
+
+#[test]
+fn test_tags_m4a() {
+    let payload = b"audio payload";
+    let path = temp_file("track.m4a", &mp4_file(payload));
+    let tags = sample_tags();
+    write_tags(&path, &tags).unwrap();
+    write_tags(&path, &tags).unwrap();
+    let read = read_tags(&path).unwrap();
+    assert_common_fields(&read, &tags);
+    assert_eq!(read.explicit, Some(false));
+    assert_eq!(read.duration, Some(215));
+    let file = fs::read(&path).unwrap();
+    let rating = file.windows(4).position(|w| w == b"rtng").unwrap();
+    // rtng, data header, type and locale, then the rating itself
+    assert_eq!(file[rating + 4 + 16], 2);
+    let offset = mp4_chunk_offset(&file);
+    assert_eq!(&file[offset..offset + payload.len()], payload);
+}
+
+#[test]
+fn test_tags_with_lyrics() {
+    let lyrics: models::gw::Lyrics = serde_json::from_value(json!({
+        "LYRICS_ID": "2780622",
+        "LYRICS_SYNC_JSON": [
+            {"lrc_timestamp": "[00:12.34]", "milliseconds": "12340", "duration": "1710", "line": "Work it harder"},
+            {"line": ""},
+            {"lrc_timestamp": "[00:14.05]", "milliseconds": "14050", "duration": "1800", "line": "Make it better"}
+        ],
+        "LYRICS_TEXT": "Work it harder\nMake it better",
+        "LYRICS_COPYRIGHTS": "Universal Music Publishing",
+        "LYRICS_WRITERS": "Thomas Bangalter, Edwin Birdsong"
+    }))
+    .unwrap();
+    let tags = TagSet::default().with_lyrics(&lyrics);
+    assert_eq!(tags.lyrics, sample_tags().lyrics);
+    assert_eq!(tags.synced_lyrics, sample_tags().synced_lyrics);
+    assert_eq!(tags.lyrics_copyright, sample_tags().lyrics_copyright);
+    assert_eq!(
+        tags.contributors_with_role("author"),
+        ["Thomas Bangalter", "Edwin Birdsong"]
+    );
+}
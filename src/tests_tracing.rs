@@ -0,0 +1,125 @@
+use crate::cache::CacheConfig;
+use crate::models::ids::ArtistId;
+use crate::transport::MockTransport;
+use crate::Deezer;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+type RecordedSpan = (String, Vec<(String, String)>);
+
+// Keeps every span with the fields recorded on it
+#[derive(Debug, Default, Clone)]
+struct Spans(Arc<Mutex<Vec<RecordedSpan>>>);
+
+impl Spans {
+    // Fields of every span with this name, in creation order
+    fn fields(&self, name: &str) -> Vec<Vec<(String, String)>> {
+        let spans = self.0.lock().unwrap();
+        spans
+            .iter()
+            .filter(|(span, _)| span == name)
+            .map(|(_, fields)| fields.clone())
+            .collect()
+    }
+}
+
+fn field<'a>(fields: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(field, _)| field == name)
+        .map(|(_, value)| value.as_str())
+}
+
+struct Fields<'a>(&'a mut Vec<(String, String)>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{value:?}")));
+    }
+}
+
+impl Subscriber for Spans {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut spans = self.0.lock().unwrap();
+        let mut fields = Vec::new();
+        span.record(&mut Fields(&mut fields));
+        spans.push((span.metadata().name().to_string(), fields));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.0.lock().unwrap();
+        let index = span.into_u64() as usize - 1;
+        values.record(&mut Fields(&mut spans[index].1));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[tokio::test]
+async fn test_tracing_spans() {
+    let spans = Spans::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let transport = MockTransport::new()
+        .with_api(
+            "artist/27",
+            r#"{"id":27,"name":"Daft Punk","tracklist":"","type":"artist"}"#,
+        )
+        .with_api("artist/27/albums?index=25", r#"{"data":[]}"#)
+        .with_api(
+            "artist/27/albums",
+            r#"{"data":[],"next":"https://api.deezer.com/artist/27/albums?index=25"}"#,
+        );
+    let deezer = Deezer::new()
+        .with_transport(transport)
+        .with_cache(CacheConfig::default());
+    deezer.artist(ArtistId(27)).await.unwrap();
+    deezer.artist(ArtistId(27)).await.unwrap();
+    assert!(deezer.artist(ArtistId(13)).await.is_err());
+    assert!(deezer.artist_albums(ArtistId(27)).await.unwrap().is_empty());
+
+    let api = spans.fields("deezer.api");
+    assert_eq!(api.len(), 5);
+    let cache_hits: Vec<_> = api.iter().map(|f| field(f, "cache_hit")).collect();
+    assert_eq!(
+        cache_hits,
+        [
+            Some("false"),
+            Some("true"),
+            Some("false"),
+            Some("false"),
+            Some("false")
+        ]
+    );
+    assert_eq!(field(&api[0], "endpoint"), Some(r#""artist""#));
+    assert_eq!(field(&api[2], "error"), Some(r#""transport""#));
+    let pages: Vec<_> = api.iter().map(|f| field(f, "page")).collect();
+    assert_eq!(pages, [None, None, None, Some("0"), Some("1")]);
+
+    let http = spans.fields("deezer.http");
+    let statuses: Vec<_> = http.iter().map(|f| field(f, "status")).collect();
+    assert_eq!(statuses, [Some("200"), None, Some("200"), Some("200")]);
+    assert_eq!(field(&http[0], "method"), Some("Get"));
+    assert!(http.iter().all(|f| field(f, "attempt") == Some("1")));
+    assert_eq!(field(&http[1], "error"), Some(r#""transport""#));
+
+    // one span per client method call
+    let artist = spans.fields("deezer.artist");
+    let ids: Vec<_> = artist.iter().map(|f| field(f, "artist_id")).collect();
+    assert_eq!(ids, [Some("27"), Some("27"), Some("13")]);
+    assert_eq!(spans.fields("deezer.artist_albums").len(), 1);
+}
@@ -0,0 +1,87 @@
+use crate::models::ids::{ArtistId, TrackId};
+use crate::transport::{HttpMethod, HttpResponse, MockTransport};
+use crate::{blocking, Deezer, DeezerError};
+use serde_json::json;
+use std::sync::Arc;
+
+fn artist_body() -> String {
+    json!({
+        "id": 27,
+        "name": "Daft Punk",
+        "tracklist": "https://api.deezer.com/artist/27/top?limit=50",
+        "type": "artist"
+    })
+    .to_string()
+}
+
+fn lyrics_body() -> String {
+    json!({
+        "results": {
+            "LYRICS_ID": "2780622",
+            "LYRICS_TEXT": "One more time",
+            "LYRICS_COPYRIGHTS": "",
+            "LYRICS_WRITERS": "Thomas Bangalter"
+        }
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_mock_transport_api() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .with_api("artist/27", artist_body())
+            .with_api("artist/13", r#"{"error":{"type":"DataException"}}"#),
+    );
+    let deezer = Deezer::new().with_transport(transport.clone());
+    assert_eq!(deezer.artist(ArtistId(27)).await.unwrap().name, "Daft Punk");
+    assert!(matches!(
+        deezer.artist(ArtistId(13)).await,
+        Err(DeezerError::ApiError(_))
+    ));
+    match deezer.artist(ArtistId(4050205)).await {
+        Err(DeezerError::TransportError(err)) => assert!(err.contains("artist/4050205")),
+        other => panic!("expected a TransportError, got {other:?}"),
+    }
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].method, HttpMethod::Get);
+    assert_eq!(requests[0].api_path(), Some("artist/27"));
+    assert_eq!(requests[0].param("limit").as_deref(), Some("100"));
+}
+
+#[tokio::test]
+async fn test_mock_transport_gw() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .with_gw(
+                "deezer.getUserData",
+                r#"{"results":{"checkForm":"mock-token"}}"#,
+            )
+            .with_gw("song.getLyrics", lyrics_body()),
+    );
+    let deezer = Deezer::new().with_transport(transport.clone());
+    let lyrics = deezer.gw_lyrics(TrackId(3135556)).await.unwrap();
+    assert_eq!(lyrics.lyrics_text, "One more time");
+    let requests = transport.requests();
+    let methods: Vec<_> = requests.iter().filter_map(|r| r.gw_method()).collect();
+    assert_eq!(methods, ["deezer.getUserData", "song.getLyrics"]);
+    assert_eq!(
+        requests[1].param("api_token").as_deref(),
+        Some("mock-token")
+    );
+    assert_eq!(requests[1].param("sng_id").as_deref(), Some("3135556"));
+}
+
+#[test]
+fn test_mock_transport_blocking() {
+    let transport = MockTransport::new()
+        .with_api("artist/27", artist_body())
+        .with_api_response("artist/13", HttpResponse::new(500, "not json"));
+    let deezer = blocking::Deezer::new().with_transport(transport);
+    assert_eq!(deezer.artist(ArtistId(27)).unwrap().id, ArtistId(27));
+    assert!(matches!(
+        deezer.artist(ArtistId(13)),
+        Err(DeezerError::JsonError(_))
+    ));
+}
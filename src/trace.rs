@@ -0,0 +1,44 @@
+// Spans are only built with the tracing feature, the no-op stand-ins below
+// keep the call sites free of cfg attributes
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::field::Empty;
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{Instrument, Span};
+
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($args:tt)*) => {
+        tracing::debug_span!($($args)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($args:tt)*) => {
+        $crate::trace::Span
+    };
+}
+
+pub(crate) use span;
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Debug, Clone)]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn record<V>(&self, _field: &str, _value: V) -> &Self {
+        self
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) trait Instrument: Sized {
+    fn instrument(self, _span: Span) -> Self {
+        self
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl<T: std::future::Future> Instrument for T {}
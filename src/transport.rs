@@ -0,0 +1,373 @@
+use reqwest::header::{self, HeaderValue, ACCEPT_LANGUAGE, USER_AGENT};
+use reqwest::{Client, Error as RequestError, Url};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::cache::api_endpoint;
+use crate::{API_URL, DEFAULT_LANGUAGE, USER_AGENT_HEADER};
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+#[derive(Debug)]
+pub enum TransportError {
+    RequestError(RequestError),
+    // failures of transports not backed by reqwest, such as a missing canned response
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HttpBody {
+    Empty,
+    Form(Vec<(String, String)>),
+    Json(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: Url,
+    // sent on top of the transport's default headers, e.g. accept-language
+    pub headers: Vec<(String, String)>,
+    pub body: HttpBody,
+}
+
+impl HttpRequest {
+    pub fn get(url: Url) -> Self {
+        Self {
+            method: HttpMethod::Get,
+            url,
+            headers: Vec::new(),
+            body: HttpBody::Empty,
+        }
+    }
+
+    pub fn post(url: Url, body: HttpBody) -> Self {
+        Self {
+            method: HttpMethod::Post,
+            url,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    // Form fields, or the query string for gw calls posting a json body
+    pub fn param(&self, name: &str) -> Option<String> {
+        if let HttpBody::Form(form) = &self.body {
+            if let Some((_, value)) = form.iter().find(|(k, _)| k == name) {
+                return Some(value.clone());
+            }
+        }
+        self.url
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    // The gw-light method name, None for api calls
+    pub fn gw_method(&self) -> Option<String> {
+        match self.method {
+            HttpMethod::Post => self.param("method"),
+            HttpMethod::Get => None,
+        }
+    }
+
+    // gw method, or the first segment of the api path such as "track"
+    pub fn endpoint(&self) -> Option<String> {
+        match self.gw_method() {
+            Some(method) => Some(method),
+            None => self.api_path().map(|path| api_endpoint(path).to_string()),
+        }
+    }
+
+    // Query pairs followed by the form fields
+    pub fn params(&self) -> Vec<(String, String)> {
+        let mut params: Vec<(String, String)> = self.url.query_pairs().into_owned().collect();
+        if let HttpBody::Form(form) = &self.body {
+            params.extend(form.iter().cloned());
+        }
+        params
+    }
+
+    // Path relative to the api root, None for other urls
+    pub fn api_path(&self) -> Option<&str> {
+        self.url
+            .as_str()
+            .strip_prefix(API_URL)
+            .map(|path| match path.split_once('?') {
+                Some((path, _)) => path,
+                None => path,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    // header names are lowercase
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    // url the body came from when the transport followed redirects
+    pub url: Option<String>,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            url: None,
+        }
+    }
+
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .push((name.to_ascii_lowercase(), value.to_string()));
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+// Sends the requests built by Deezer, the cookies needed by gw-light are the
+// transport's responsibility
+pub trait Transport: Debug + Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_HEADER));
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static(DEFAULT_LANGUAGE));
+
+        let client = Client::builder()
+            .cookie_store(true)
+            .default_headers(headers)
+            .timeout(Duration::from_secs(15))
+            .build()
+            .expect("Could not establish client!");
+        Self { client }
+    }
+
+    // The client needs a cookie store for gw-light calls to work
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    async fn send_request(&self, request: HttpRequest) -> Result<HttpResponse, RequestError> {
+        let builder = match request.method {
+            HttpMethod::Get => self.client.get(request.url),
+            HttpMethod::Post => self.client.post(request.url),
+        };
+        let builder = request
+            .headers
+            .iter()
+            .fold(builder, |builder, (name, value)| {
+                builder.header(name, value)
+            });
+        let builder = match &request.body {
+            HttpBody::Empty => builder,
+            HttpBody::Form(form) => builder.form(form),
+            HttpBody::Json(body) => builder.json(body),
+        };
+        let response = builder.send().await?;
+        let status = response.status().as_u16();
+        let url = Some(response.url().to_string());
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.as_str().to_string(), value.to_string()))
+            })
+            .collect();
+        let body = response.bytes().await?.to_vec();
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+            url,
+        })
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            self.send_request(request)
+                .await
+                .map_err(TransportError::RequestError)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Route {
+    // api path and the query pairs which must be present
+    Api(String, Vec<(String, String)>),
+    Gw(String),
+    Url(String),
+}
+
+impl Route {
+    fn matches(&self, request: &HttpRequest) -> bool {
+        match self {
+            Route::Api(path, query) => {
+                request.api_path() == Some(path.as_str())
+                    && query.iter().all(|(k, v)| {
+                        request
+                            .url
+                            .query_pairs()
+                            .any(|(rk, rv)| rk == *k && rv == *v)
+                    })
+            }
+            Route::Gw(method) => request.gw_method().as_deref() == Some(method.as_str()),
+            Route::Url(url) => request.url.as_str() == url,
+        }
+    }
+}
+
+// In-memory transport answering requests with canned responses, the first
+// matching route wins and unmatched requests fail
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    routes: Vec<(Route, HttpResponse)>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // path is relative to the api root, query pairs given in it must all be
+    // present in the request, e.g. "search/track?q=one"
+    pub fn with_api(self, path: &str, body: impl Into<String>) -> Self {
+        self.with_api_response(path, HttpResponse::ok(body.into()))
+    }
+
+    pub fn with_api_response(mut self, path: &str, response: HttpResponse) -> Self {
+        let (path, query) = match path.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (path, ""),
+        };
+        let query = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        self.routes
+            .push((Route::Api(path.to_string(), query), response));
+        self
+    }
+
+    pub fn with_gw(self, method: &str, body: impl Into<String>) -> Self {
+        self.with_gw_response(method, HttpResponse::ok(body.into()))
+    }
+
+    pub fn with_gw_response(mut self, method: &str, response: HttpResponse) -> Self {
+        self.routes.push((Route::Gw(method.to_string()), response));
+        self
+    }
+
+    // Exact url match, for requests outside the apis such as cover_art
+    pub fn with_url_response(mut self, url: &str, response: HttpResponse) -> Self {
+        self.routes.push((Route::Url(url.to_string()), response));
+        self
+    }
+
+    // Every request sent so far, in order
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn respond(&self, request: HttpRequest) -> Result<HttpResponse, TransportError> {
+        let route = self
+            .routes
+            .iter()
+            .find(|(route, _)| route.matches(&request));
+        let response = match route {
+            Some((_, response)) => Ok(response.clone()),
+            None => Err(TransportError::Other(format!(
+                "no canned response for {:?} {}",
+                request.method, request.url
+            ))),
+        };
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(request);
+        response
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move { self.respond(request) })
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+pub(crate) fn form_body(params: HashMap<&str, String>) -> HttpBody {
+    HttpBody::Form(
+        params
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}